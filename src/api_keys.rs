@@ -0,0 +1,186 @@
+use crate::errors::RequestError;
+use crate::rate_limiting::{RateLimitDecision, RateLimiter, TokenBucket};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Header carrying the API key of the calling client.
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// Configuration of an API key.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ApiKeyConfig {
+    /// Identity of the key owner, used in logs (e.g. `team-roster`).
+    pub name: String,
+    pub key: String,
+    /// Max number of requests per minute, unlimited if missing.
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Max number of requests per UTC day, unlimited if missing. Batch requests count one per Pokémon not cached (at
+    /// least one).
+    #[serde(default)]
+    pub daily_quota: Option<u64>,
+}
+
+/// API keys allowed to call the API services, each with its own rate and daily quota.
+pub struct ApiKeys {
+    /// Keys by name of their owner.
+    keys: HashMap<String, ApiKey>,
+}
+
+struct ApiKey {
+    config: ApiKeyConfig,
+    /// SHA-256 of the key, compared in constant time with the one of the request key.
+    digest: Vec<u8>,
+    usage: Mutex<ApiKeyUsage>,
+}
+
+struct ApiKeyUsage {
+    /// Bucket of the requests per minute, `None` if unlimited.
+    bucket: Option<TokenBucket>,
+    day: NaiveDate,
+    day_requests: u64,
+}
+
+impl ApiKeys {
+    /// Panics in case of duplicated keys or names.
+    pub fn new(configs: Vec<ApiKeyConfig>) -> Self {
+        let mut keys = HashMap::new();
+        let mut digests = HashSet::new();
+        for config in configs {
+            let digest = key_digest(&config.key);
+            if !digests.insert(digest.clone()) {
+                panic!("Duplicated API key of {}", config.name);
+            }
+            let usage = Mutex::new(ApiKeyUsage {
+                bucket: config
+                    .requests_per_minute
+                    .map(|rate| TokenBucket::new(rate, rate)),
+                day: Utc::now().naive_utc().date(),
+                day_requests: 0,
+            });
+            let name = config.name.clone();
+            let api_key = ApiKey {
+                config,
+                digest,
+                usage,
+            };
+            if keys.insert(name.clone(), api_key).is_some() {
+                panic!("Duplicated API key name {}", name);
+            }
+        }
+        Self { keys }
+    }
+
+    /// Reads the API keys from the JSON array of `ApiKeyConfig` in `POKESPEARE_API_KEYS` and from the JSON file at
+    /// `POKESPEARE_API_KEYS_FILE`, returning `None` if both are missing, meaning that authentication is disabled.
+    ///
+    /// Panics in case of invalid env vars or unreadable file.
+    pub fn from_env() -> Option<Self> {
+        let from_var = std::env::var("POKESPEARE_API_KEYS").ok().map(|keys| {
+            serde_json::from_str::<Vec<ApiKeyConfig>>(&keys)
+                .unwrap_or_else(|e| panic!("Invalid POKESPEARE_API_KEYS, error: {:?}", e))
+        });
+        let from_file = std::env::var("POKESPEARE_API_KEYS_FILE").ok().map(|path| {
+            let keys = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("Can't read API keys file {}, error: {:?}", path, e));
+            serde_json::from_str::<Vec<ApiKeyConfig>>(&keys)
+                .unwrap_or_else(|e| panic!("Invalid API keys file {}, error: {:?}", path, e))
+        });
+        if from_var.is_none() && from_file.is_none() {
+            return None;
+        }
+        Some(Self::new(
+            from_var.into_iter().chain(from_file).flatten().collect(),
+        ))
+    }
+
+    /// Authenticates a request with the given API key, returning the name of the key owner or an `Unauthorized` error
+    /// for missing or unknown keys.
+    ///
+    /// The key is compared with every configured one in constant time, not to leak how much of it matches. Requests are
+    /// counted against the key limits by `limit`, once authenticated.
+    pub fn authenticate(&self, key: Option<&str>) -> Result<&str, RequestError> {
+        let key = key.ok_or_else(|| {
+            RequestError::Unauthorized(format!("Missing {} header", API_KEY_HEADER))
+        })?;
+        let digest = key_digest(key);
+        let mut authenticated = None;
+        for api_key in self.keys.values() {
+            if constant_time_eq(&api_key.digest, &digest) {
+                authenticated = Some(api_key.config.name.as_str());
+            }
+        }
+        authenticated.ok_or_else(|| RequestError::Unauthorized("Unknown API key".into()))
+    }
+
+    /// Limits a request authenticated with the key of the given owner: first by the key daily quota, and then by the
+    /// key rate or, if it has none, by the given `RateLimiter` (per key name). The request takes `cost` tokens of the
+    /// rate and counts as `cost` requests against the quota, either both or none: requests exceeding the quota take no
+    /// tokens, while the ones exceeding the rate don't count against the quota.
+    ///
+    /// Returns the decision of the applied rate limit, if any, to be reported to the client, together with the outcome:
+    /// `TooManyRequests` for exceeded rate and `QuotaExceeded` for exceeded daily quota.
+    pub fn limit(
+        &self,
+        name: &str,
+        rate_limiter: Option<&RateLimiter>,
+        cost: u32,
+    ) -> (Option<RateLimitDecision>, Result<(), RequestError>) {
+        let api_key = match self.keys.get(name) {
+            Some(api_key) => api_key,
+            None => {
+                let error = RequestError::Unauthorized(format!("Unknown API key of {}", name));
+                return (None, Err(error));
+            }
+        };
+        let config = &api_key.config;
+        let mut usage = api_key.usage.lock().unwrap();
+
+        let today = Utc::now().naive_utc().date();
+        if usage.day != today {
+            usage.day = today;
+            usage.day_requests = 0;
+        }
+        if let Some(daily_quota) = config.daily_quota {
+            if usage.day_requests + u64::from(cost) > daily_quota {
+                let error = RequestError::QuotaExceeded(format!(
+                    "API key of {} exceeded its daily quota of {} requests",
+                    config.name, daily_quota
+                ));
+                return (None, Err(error));
+            }
+        }
+
+        let (decision, requests_per_minute) = match (&mut usage.bucket, rate_limiter) {
+            (Some(bucket), _) => (Some(bucket.check(cost)), config.requests_per_minute),
+            (None, Some(rate_limiter)) => (
+                Some(rate_limiter.check(&format!("api_key:{}", name), cost)),
+                Some(rate_limiter.requests_per_minute()),
+            ),
+            (None, None) => (None, None),
+        };
+        if let (Some(decision), Some(requests_per_minute)) = (&decision, requests_per_minute) {
+            if !decision.allowed {
+                let error = RequestError::TooManyRequests(format!(
+                    "API key of {} exceeded its rate of {} requests per minute",
+                    config.name, requests_per_minute
+                ));
+                return (Some(decision.clone()), Err(error));
+            }
+        }
+        usage.day_requests += u64::from(cost);
+        (decision, Ok(()))
+    }
+}
+
+fn key_digest(key: &str) -> Vec<u8> {
+    Sha256::digest(key.as_bytes()).to_vec()
+}
+
+/// Compares the given bytes looking at all of them, whether they match or not.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
@@ -0,0 +1,30 @@
+use actix_slog::StructuredLogger;
+use actix_web::{App, HttpServer};
+use pokespeare::log_helpers::*;
+use pokespeare::upstream_stub::{self, StubConfig};
+
+/// Local stand-in of PokeApi and FunTranslations APIs, to run the server end to end offline.
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let (log, _log_guard) = get_root_logger(&LogConfig::from_env());
+
+    let listen_addr = std::env::var("UPSTREAM_STUB_LISTEN_ADDR")
+        .expect("Missing required UPSTREAM_STUB_LISTEN_ADDR");
+    let stub_config = StubConfig::from_env();
+
+    info!(log, "Start upstream stub";
+        "listen_addr" => &listen_addr,
+        "fixtures_dir" => ?stub_config.fixtures_dir,
+        "latency_ms" => stub_config.latency.as_millis() as u64,
+        "error_rate" => stub_config.error_rate,
+        "too_many_requests_rate" => stub_config.too_many_requests_rate,
+    );
+    HttpServer::new(move || {
+        App::new()
+            .wrap(StructuredLogger::new(log.clone()))
+            .configure(|cfg| upstream_stub::config_stub(cfg, stub_config.clone()))
+    })
+    .bind(listen_addr)?
+    .run()
+    .await
+}
@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Makes room for a new entry in a map holding up to `max_entries`, evicting the least recently used ones.
+pub fn evict_least_recently_used<V>(
+    map: &mut HashMap<String, V>,
+    max_entries: usize,
+    used_at: impl Fn(&V) -> Instant,
+) {
+    while map.len() >= max_entries {
+        let least_recently_used = map
+            .iter()
+            .min_by_key(|(_, value)| used_at(value))
+            .map(|(key, _)| key.clone());
+        match least_recently_used {
+            Some(key) => map.remove(&key),
+            None => break,
+        };
+    }
+}
@@ -0,0 +1,244 @@
+use crate::log_helpers::{error, Logger};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Client, Error as ReqwestError, RequestBuilder, Response, ResponseBuilderExt};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+
+/// Response headers kept in recorded interactions: the others (e.g. dates, cookies or rate limits of the upstreams)
+/// would only make cassettes noisy.
+const RECORDED_HEADERS: &[&str] = &["content-type", "etag", "last-modified", "retry-after"];
+
+/// Whether upstream calls are recorded or replayed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CassetteMode {
+    /// Calls reach the upstreams and their request/response pairs are saved as cassettes.
+    Record,
+    /// Calls never reach the upstreams and are served from the saved cassettes.
+    Replay,
+}
+
+/// Directory of cassettes, each holding a recorded request/response pair of an upstream call.
+///
+/// Requests are matched by method, path, query and body, so that cassettes don't depend on the endpoint of the upstream
+/// they were recorded from. Request headers are never recorded, as they could carry secrets (e.g. `ApiSecret`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cassettes {
+    pub dir: PathBuf,
+    pub mode: CassetteMode,
+}
+
+impl Cassettes {
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Record,
+        }
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Replay,
+        }
+    }
+
+    /// Reads the cassettes from the env, returning `None` if `UPSTREAM_CASSETTES_DIR` is not set.
+    ///
+    /// The mode is read from `UPSTREAM_CASSETTES_MODE`, either `record` or `replay` (default).
+    ///
+    /// Panics in case of invalid env vars.
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("UPSTREAM_CASSETTES_DIR").ok()?;
+        match std::env::var("UPSTREAM_CASSETTES_MODE").as_deref() {
+            Ok("record") => Some(Self::record(dir)),
+            Ok("replay") | Err(_) => Some(Self::replay(dir)),
+            Ok(mode) => panic!("Invalid UPSTREAM_CASSETTES_MODE {:?}", mode),
+        }
+    }
+
+    /// Sends the request according to the mode, recording its response or replaying the recorded one.
+    ///
+    /// When replaying a request without cassette, the response is a `501 Not Implemented` naming the missing
+    /// cassette. Failures in saving cassettes don't fail the calls and are logged instead.
+    pub async fn send(&self, req: RequestBuilder, log: &Logger) -> Result<Response, ReqwestError> {
+        let req = req.build()?;
+        let url = req.url().clone();
+        let recorded_request = RecordedRequest {
+            method: req.method().to_string(),
+            url: match req.url().query() {
+                Some(query) => format!("{}?{}", req.url().path(), query),
+                None => req.url().path().to_string(),
+            },
+            body: req
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+        };
+        let path = self.dir.join(recorded_request.file_name());
+
+        match self.mode {
+            CassetteMode::Replay => Ok(match Interaction::load(&path) {
+                Ok(interaction) => interaction.response.into_response(url),
+                Err(e) => RecordedResponse::unavailable(&path, e).into_response(url),
+            }),
+            CassetteMode::Record => {
+                let resp = Client::new().execute(req).await?;
+                let status = resp.status().as_u16();
+                let headers = resp
+                    .headers()
+                    .iter()
+                    .filter(|(name, _)| RECORDED_HEADERS.contains(&name.as_str()))
+                    .filter_map(|(name, value)| {
+                        Some((name.to_string(), value.to_str().ok()?.into()))
+                    })
+                    .collect();
+                let bytes = resp.bytes().await?;
+                let interaction = Interaction {
+                    request: recorded_request,
+                    response: RecordedResponse::new(status, headers, &bytes),
+                };
+                if let Err(e) = interaction.save(&path) {
+                    error!(log, "Can't save cassette"; "path" => ?path, "error" => %e);
+                }
+                // The interaction is replayed also when recording, as the body of the response has been consumed
+                Ok(interaction.response.into_response(url))
+            }
+        }
+    }
+}
+
+/// Recorded request/response pair of an upstream call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+impl Interaction {
+    /// Loads a cassette, e.g. to use its recorded response in tests.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, IoError> {
+        let cassette = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&cassette)?)
+    }
+
+    fn save(&self, path: &Path) -> Result<(), IoError> {
+        std::fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))?;
+        let mut cassette = serde_json::to_vec_pretty(self)?;
+        cassette.push(b'\n');
+        std::fs::write(path, cassette)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query of the request URL, without the upstream endpoint.
+    pub url: String,
+    pub body: Option<String>,
+}
+
+impl RecordedRequest {
+    /// Name of the cassette of the request, readable and yet unique thanks to the hash of the whole request.
+    pub fn file_name(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.method.as_bytes());
+        hasher.update(b" ");
+        hasher.update(self.url.as_bytes());
+        if let Some(body) = &self.body {
+            hasher.update(b"\n");
+            hasher.update(body.as_bytes());
+        }
+        let hash = hasher.finalize();
+        let readable_url: String = self
+            .url
+            .trim_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .take(80)
+            .collect();
+        format!(
+            "{}_{}_{:02x}{:02x}{:02x}{:02x}.json",
+            self.method.to_lowercase(),
+            readable_url,
+            hash[0],
+            hash[1],
+            hash[2],
+            hash[3]
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// JSON bodies are recorded as they are, to be easily read and edited, while the others as strings.
+    pub body: Value,
+}
+
+impl RecordedResponse {
+    fn new(status: u16, headers: BTreeMap<String, String>, bytes: &[u8]) -> Self {
+        let body = match serde_json::from_slice(bytes) {
+            Ok(json) if is_json(&headers) => json,
+            _ => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+        };
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    fn unavailable(path: &Path, error: IoError) -> Self {
+        let (status, message) = match error.kind() {
+            std::io::ErrorKind::NotFound => (501, format!("No cassette {:?}", path)),
+            _ => (
+                500,
+                format!("Can't load cassette {:?}, error: {}", path, error),
+            ),
+        };
+        Self {
+            status,
+            headers: BTreeMap::new(),
+            body: Value::String(message),
+        }
+    }
+
+    fn body_bytes(&self) -> Vec<u8> {
+        match &self.body {
+            Value::String(text) if !is_json(&self.headers) => text.clone().into_bytes(),
+            json => json.to_string().into_bytes(),
+        }
+    }
+
+    /// Builds the response to a request to the given URL, reported by its errors (e.g. `error_for_status`) and logs.
+    fn into_response(self, url: Url) -> Response {
+        let mut resp = http::Response::builder()
+            .status(StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+            .url(url)
+            .body(self.body_bytes())
+            .expect("Valid status and no headers");
+        let headers: &mut HeaderMap = resp.headers_mut();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        resp.into()
+    }
+}
+
+fn is_json(headers: &BTreeMap<String, String>) -> bool {
+    headers
+        .get(CONTENT_TYPE.as_str())
+        .map(|content_type| content_type.contains("json"))
+        .unwrap_or(false)
+}
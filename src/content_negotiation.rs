@@ -0,0 +1,157 @@
+use crate::services_api_models::ShakespeareanDescriptionApiResponse;
+use std::fmt::Display;
+
+/// Picks, among the `available` media types (in order of preference), the one best matching the given `Accept`
+/// header value.
+///
+/// Each available media type gets the quality of the most specific matching media range (e.g. `text/html` over
+/// `text/*` over `*/*`) and the one with the highest non-zero quality wins, with ties broken by preference.
+/// Without `Accept` header, the first available media type is picked.
+/// Returns `None` if none of the available media types is acceptable.
+pub fn negotiate<'a>(accept: Option<&str>, available: &[&'a str]) -> Option<&'a str> {
+    let accept = match accept {
+        Some(accept) if !accept.trim().is_empty() => accept,
+        _ => return available.first().copied(),
+    };
+    let media_ranges = accept
+        .split(',')
+        .filter_map(parse_media_range)
+        .collect::<Vec<_>>();
+
+    let mut best: Option<(&str, f32)> = None;
+    for &media_type in available {
+        let quality = media_ranges
+            .iter()
+            .filter_map(|(range, quality)| {
+                specificity(range, media_type).map(|specificity| (specificity, *quality))
+            })
+            .max_by_key(|&(specificity, _)| specificity)
+            .map_or(0.0, |(_, quality)| quality);
+        match best {
+            Some((_, best_quality)) if best_quality >= quality => {}
+            _ if quality > 0.0 => best = Some((media_type, quality)),
+            _ => {}
+        }
+    }
+    best.map(|(media_type, _)| media_type)
+}
+
+/// Parses a media range of an `Accept` header value into its lowercase media type and its quality (`q` parameter,
+/// 1 by default).
+fn parse_media_range(media_range: &str) -> Option<(String, f32)> {
+    let mut parts = media_range.split(';');
+    let range = parts.next()?.trim().to_ascii_lowercase();
+    if range.is_empty() {
+        return None;
+    }
+    let quality = parts
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            if name.trim() == "q" {
+                value.trim().parse::<f32>().ok()
+            } else {
+                None
+            }
+        })
+        .next()
+        .unwrap_or(1.0);
+    Some((range, quality))
+}
+
+/// Returns how specifically the media range matches the media type, if it does at all.
+fn specificity(range: &str, media_type: &str) -> Option<u8> {
+    if range == media_type {
+        return Some(2);
+    }
+    if range == "*/*" {
+        return Some(0);
+    }
+    let range_type = range.strip_suffix("/*")?;
+    if media_type.split('/').next() == Some(range_type) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Renders the description as a small standalone HTML page quoting it, meant to be embedded (e.g. in an `iframe`).
+///
+/// Forms and varieties are captioned with the name of their species too, while stale descriptions are marked as
+/// possibly outdated.
+pub fn render_html(description: &ShakespeareanDescriptionApiResponse) -> String {
+    let species_name = match &description.species_name {
+        Some(species_name) => format!(
+            " <span class=\"species-name\">({})</span>",
+            escape_markup(species_name)
+        ),
+        None => String::new(),
+    };
+    let stale = if description.stale {
+        "\n    <p class=\"stale\">Possibly outdated: PokeApi is unavailable</p>"
+    } else {
+        ""
+    };
+    include_str!("../static/description_embed.html")
+        .replace("{{name}}", &escape_markup(&description.name))
+        .replace("{{species_name}}", &species_name)
+        .replace("{{stale}}", stale)
+        .replace("{{description}}", &escape_markup(&description.description))
+}
+
+/// Renders the description as an XML document with the same fields of its JSON representation, species data
+/// included.
+pub fn render_xml(description: &ShakespeareanDescriptionApiResponse) -> String {
+    let mut xml =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<shakespeareanDescription>");
+    push_xml_element(&mut xml, "name", Some(&description.name));
+    push_xml_element(&mut xml, "description", Some(&description.description));
+    push_xml_element(&mut xml, "speciesName", description.species_name.as_ref());
+    if description.stale {
+        push_xml_element(&mut xml, "stale", Some(&true));
+    }
+    if let Some(species) = &description.species {
+        xml.push_str("<species>");
+        push_xml_element(&mut xml, "genus", species.genus.as_ref());
+        push_xml_element(
+            &mut xml,
+            "shakespeareanGenus",
+            species.shakespearean_genus.as_ref(),
+        );
+        push_xml_element(&mut xml, "generation", species.generation.as_ref());
+        push_xml_element(&mut xml, "color", species.color.as_ref());
+        push_xml_element(&mut xml, "habitat", species.habitat.as_ref());
+        push_xml_element(&mut xml, "isLegendary", species.is_legendary.as_ref());
+        push_xml_element(&mut xml, "isMythical", species.is_mythical.as_ref());
+        push_xml_element(&mut xml, "pokedexNumber", species.pokedex_number.as_ref());
+        xml.push_str("</species>");
+    }
+    xml.push_str("</shakespeareanDescription>\n");
+    xml
+}
+
+/// Appends the element with the given name and value to the XML document, only if there's a value.
+fn push_xml_element(xml: &mut String, name: &str, value: Option<&impl Display>) {
+    if let Some(value) = value {
+        xml.push_str(&format!(
+            "<{name}>{}</{name}>",
+            escape_markup(&value.to_string()),
+            name = name
+        ));
+    }
+}
+
+/// Escapes the characters with special meaning in HTML and XML.
+fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
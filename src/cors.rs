@@ -0,0 +1,128 @@
+use crate::env_helpers::parse_env_var;
+use actix_cors::Cors;
+use actix_web::http::header::{HeaderName, HeaderValue, CACHE_CONTROL, ETAG, LINK, RETRY_AFTER};
+use actix_web::http::Method;
+
+/// CORS configuration of the API services.
+#[derive(Clone, Debug)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API services: exact ones (e.g. `https://pokespeare.dev`), with a wildcard
+    /// standing for any subdomain (e.g. `https://*.pokespeare.dev`) or `*` for any.
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    /// Whether requests with credentials (e.g. cookies) are allowed.
+    pub allow_credentials: bool,
+    /// How long preflight responses can be cached.
+    pub max_age_secs: Option<usize>,
+}
+
+impl CorsConfig {
+    /// Reads the configuration from the env, returning `None` if `POKESPEARE_CORS_ALLOWED_ORIGINS` (comma separated
+    /// origins) is not set, meaning that CORS is disabled.
+    ///
+    /// The other env vars are:
+    /// - `POKESPEARE_CORS_ALLOWED_METHODS`: comma separated methods (`GET, POST` by default)
+    /// - `POKESPEARE_CORS_ALLOWED_HEADERS`: comma separated headers
+    ///   (`Accept, Content-Type, If-None-Match, X-Api-Key` by default)
+    /// - `POKESPEARE_CORS_ALLOW_CREDENTIALS`: `true` or `false` (default)
+    /// - `POKESPEARE_CORS_MAX_AGE_SECS`: max age of preflight responses (1 hour by default)
+    ///
+    /// Panics in case of invalid env vars or if credentials are allowed together with any origin.
+    pub fn from_env() -> Option<Self> {
+        let allowed_origins = split_list(&std::env::var("POKESPEARE_CORS_ALLOWED_ORIGINS").ok()?);
+        let allowed_methods = split_list(
+            &std::env::var("POKESPEARE_CORS_ALLOWED_METHODS")
+                .unwrap_or_else(|_| "GET, POST".into()),
+        )
+        .iter()
+        .map(|method| {
+            method
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid POKESPEARE_CORS_ALLOWED_METHODS {:?}", method))
+        })
+        .collect();
+        let allowed_headers = split_list(
+            &std::env::var("POKESPEARE_CORS_ALLOWED_HEADERS")
+                .unwrap_or_else(|_| "Accept, Content-Type, If-None-Match, X-Api-Key".into()),
+        )
+        .iter()
+        .map(|header| {
+            header
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid POKESPEARE_CORS_ALLOWED_HEADERS {:?}", header))
+        })
+        .collect();
+        let allow_credentials = parse_env_var("POKESPEARE_CORS_ALLOW_CREDENTIALS").unwrap_or(false);
+        if allow_credentials && allowed_origins.iter().any(|origin| origin == "*") {
+            panic!("POKESPEARE_CORS_ALLOW_CREDENTIALS can't be true with any origin (*) allowed");
+        }
+
+        Some(Self {
+            allowed_origins,
+            allowed_methods,
+            allowed_headers,
+            allow_credentials,
+            max_age_secs: Some(parse_env_var("POKESPEARE_CORS_MAX_AGE_SECS").unwrap_or(3600)),
+        })
+    }
+
+    /// Builds the CORS middleware, also handling preflight requests.
+    ///
+    /// Requests from not allowed origins are rejected with a `400 Bad Request`. Besides the CORS-safelisted ones, the
+    /// caching, versioning and rate limiting response headers are exposed to the callers.
+    pub fn cors(&self) -> Cors {
+        let mut cors = Cors::default()
+            .allowed_methods(self.allowed_methods.clone())
+            .allowed_headers(self.allowed_headers.clone())
+            .expose_headers(vec![
+                ETAG,
+                CACHE_CONTROL,
+                LINK,
+                HeaderName::from_static("deprecation"),
+                HeaderName::from_static("sunset"),
+                HeaderName::from_static("ratelimit-limit"),
+                HeaderName::from_static("ratelimit-remaining"),
+                HeaderName::from_static("ratelimit-reset"),
+                RETRY_AFTER,
+            ])
+            .max_age(self.max_age_secs);
+        if self.allow_credentials {
+            cors = cors.supports_credentials();
+        }
+
+        for origin in &self.allowed_origins {
+            cors = if origin == "*" {
+                cors.allow_any_origin().send_wildcard()
+            } else if origin.contains('*') {
+                let pattern = origin.clone();
+                cors.allowed_origin_fn(move |origin, _| matches_wildcard_origin(&pattern, origin))
+            } else {
+                cors.allowed_origin(origin)
+            };
+        }
+        cors
+    }
+}
+
+/// Whether the origin matches the pattern, whose (single) `*` stands for one or more subdomains.
+fn matches_wildcard_origin(pattern: &str, origin: &HeaderValue) -> bool {
+    let (origin, (prefix, suffix)) = match (origin.to_str(), pattern.split_once('*')) {
+        (Ok(origin), Some(parts)) => (origin, parts),
+        _ => return false,
+    };
+    origin.len() > prefix.len() + suffix.len()
+        && origin.starts_with(prefix)
+        && origin.ends_with(suffix)
+        && origin[prefix.len()..origin.len() - suffix.len()]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,178 @@
+use crate::cache_helpers::evict_least_recently_used;
+use crate::env_helpers::parse_env_var;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default max number of `DescriptionsCache` entries: room for the descriptions, genera and evolution narratives of
+/// all the Pokémon known by PokeApi API.
+pub const DEFAULT_CACHE_MAX_ENTRIES: usize = 10_000;
+
+/// In-memory cache of the Pokémon "Shakespearean" descriptions, keyed by Pokémon name.
+///
+/// FunTranslations API limits are tight, so once translated a description is reused until its TTL expires, also
+/// across restarts if the cache is persisted to a file. It's shared by all the API services translating Pokémon data,
+/// up to a max number of entries: beyond it, the least recently used ones are evicted.
+pub struct DescriptionsCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, CachedDescription>>,
+    file: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedDescription {
+    description: String,
+    /// Wall clock time, so that it's still meaningful once restored by another process.
+    cached_at: SystemTime,
+    #[serde(skip, default = "Instant::now")]
+    used_at: Instant,
+}
+
+impl CachedDescription {
+    fn is_expired(&self, ttl: Duration) -> bool {
+        // A clock going backwards makes the entry look just cached
+        self.cached_at.elapsed().unwrap_or_default() >= ttl
+    }
+}
+
+impl DescriptionsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_CACHE_MAX_ENTRIES,
+            entries: Mutex::new(HashMap::new()),
+            file: None,
+        }
+    }
+
+    /// Sets the max number of cached entries, `DEFAULT_CACHE_MAX_ENTRIES` by default.
+    ///
+    /// Panics if 0.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        if max_entries == 0 {
+            panic!("The max number of descriptions cache entries must be greater than 0");
+        }
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Persists the cache to the given file with `persist` and restores it from there with `restore`.
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Builds the cache with the TTL read from `POKESPEARE_CACHE_TTL_SECS` (1 day by default, 0 disables the cache), the
+    /// max number of entries read from `POKESPEARE_CACHE_MAX_ENTRIES` (`DEFAULT_CACHE_MAX_ENTRIES` by default) and the
+    /// file read from `POKESPEARE_CACHE_FILE` (not persisted by default).
+    ///
+    /// Panics in case of invalid env vars.
+    pub fn from_env() -> Self {
+        let cache = Self::new(Duration::from_secs(
+            parse_env_var("POKESPEARE_CACHE_TTL_SECS").unwrap_or(24 * 60 * 60),
+        ))
+        .with_max_entries(
+            parse_env_var("POKESPEARE_CACHE_MAX_ENTRIES").unwrap_or(DEFAULT_CACHE_MAX_ENTRIES),
+        );
+        match std::env::var("POKESPEARE_CACHE_FILE") {
+            Ok(file) => cache.with_file(file),
+            Err(_) => cache,
+        }
+    }
+
+    /// Returns the cached description of the given Pokémon, if any and not expired.
+    pub fn get(&self, pokemon_name: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_mut(pokemon_name) {
+            Some(entry) if !entry.is_expired(self.ttl) => {
+                entry.used_at = Instant::now();
+                Some(entry.description.clone())
+            }
+            Some(_) => {
+                entries.remove(pokemon_name);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches the description of the given Pokémon, unless the TTL is zero (i.e. the cache is disabled).
+    pub fn insert(&self, pokemon_name: &str, description: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let entry = CachedDescription {
+            description: description.into(),
+            cached_at: SystemTime::now(),
+            used_at: Instant::now(),
+        };
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.contains_key(pokemon_name) {
+            evict_least_recently_used(&mut entries, self.max_entries, |entry| entry.used_at);
+        }
+        entries.insert(pokemon_name.into(), entry);
+    }
+
+    /// Number of cached entries, expired ones included.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes the not expired entries to the cache file as JSON, returning how many were written or `None` if the
+    /// cache has no file.
+    pub fn persist(&self) -> Result<Option<usize>, IoError> {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        let entries = self.entries.lock().unwrap();
+        let entries = entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_expired(self.ttl))
+            .collect::<HashMap<_, _>>();
+        // Written aside and then renamed, not to leave a truncated file if the process is killed meanwhile
+        let tmp_file = file.with_extension("tmp");
+        std::fs::write(&tmp_file, serde_json::to_vec(&entries)?)?;
+        std::fs::rename(&tmp_file, file)?;
+        Ok(Some(entries.len()))
+    }
+
+    /// Adds the not expired entries of the cache file, up to the max number of entries, returning how many were added or
+    /// `None` if the cache has no file or it doesn't exist yet.
+    pub fn restore(&self) -> Result<Option<usize>, IoError> {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        let json = match std::fs::read(file) {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut restored = serde_json::from_slice::<HashMap<String, CachedDescription>>(&json)?
+            .into_iter()
+            .filter(|(_, entry)| !entry.is_expired(self.ttl))
+            .collect::<Vec<_>>();
+        // Only the most recently cached ones, if the file holds more than the max number of entries
+        restored.sort_by_key(|(_, entry)| Reverse(entry.cached_at));
+        restored.truncate(self.max_entries);
+        let count = restored.len();
+        let mut entries = self.entries.lock().unwrap();
+        for (pokemon_name, entry) in restored {
+            if !entries.contains_key(&pokemon_name) {
+                evict_least_recently_used(&mut entries, self.max_entries, |entry| entry.used_at);
+            }
+            entries.insert(pokemon_name, entry);
+        }
+        Ok(Some(count))
+    }
+}
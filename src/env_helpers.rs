@@ -0,0 +1,9 @@
+/// Parses the env var `name`, returning `None` if it's not set.
+///
+/// Panics if the env var is set but can't be parsed.
+pub fn parse_env_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().map(|v| {
+        v.parse()
+            .unwrap_or_else(|_| panic!("Invalid {} {:?}", name, v))
+    })
+}
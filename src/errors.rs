@@ -1,84 +1,360 @@
-use crate::fun_translations_client::FunTranslationsClientError;
-use crate::poke_api_client::PokeApiClientError;
-use actix_web::error::ResponseError;
-use actix_web::http::StatusCode;
-use actix_web::HttpResponse;
+use crate::upstreams::{DescriptionSourceError, TranslatorError};
+#[cfg(feature = "server")]
+use crate::{
+    fun_translations_client::FunTranslationsClientError, poke_api_client::PokeApiClientError,
+};
+#[cfg(feature = "server")]
+use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
+#[cfg(feature = "server")]
 use reqwest::StatusCode as ReqwestStatusCode;
 use serde::{Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use utoipa::ToSchema;
 
 /// Representation of an API error response body.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
 pub struct ApiErrorResponseBody {
     pub code: ApiErrorResponseCode,
+    /// Indicative error detail.
     pub message: String,
+    /// Whether a rate limited request (i.e. `TOO_MANY_REQUESTS` or `QUOTA_EXCEEDED`) was limited by this service or by
+    /// its upstreams, missing for other errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limited_by: Option<RateLimitedBy>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// Who limited a rate limited request.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RateLimitedBy {
+    /// This service, because of the limits of the client (e.g. per IP or API key).
+    Local,
+    /// An upstream API (e.g. FunTranslations API), because of its limits.
+    Upstream,
+}
+
+/// Descriptive code of an API error.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ApiErrorResponseCode {
     TranslatableDescriptionNotFound,
+    /// PokeApi API knows no evolution chain of the Pokémon species.
+    EvolutionChainNotFound,
     PokeApiError,
     FunTranslationsError,
+    /// FunTranslations API rejected the configured paid plan secret.
+    FunTranslationsUnauthorized,
     TooManyRequests,
+    InvalidRequest,
+    PayloadTooLarge,
+    NotAcceptable,
+    Unauthorized,
+    QuotaExceeded,
+    /// Unexpected error of a description source other than PokeApi API.
+    DescriptionSourceError,
+    /// Unexpected error of a translator other than FunTranslations API.
+    TranslatorError,
+}
+
+/// Errors returned by the API services as JSON `ApiErrorResponseBody`s.
+///
+/// `ResponseError::error_response` is expected to build its response from `status_code` and `api_error_response_body`,
+/// which are also used to report errors inside successful responses (e.g. batch ones).
+#[cfg(feature = "server")]
+pub trait ApiError: ResponseError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody;
 }
 
 /// Make `PokeApiClientError` an `actix_web` "citizen" by implementing `actix_web::error::ResponseError`.
+#[cfg(feature = "server")]
 impl ResponseError for PokeApiClientError {
     fn status_code(&self) -> StatusCode {
         match self {
-            PokeApiClientError::TraslatableDescriptionNotFound(_) => StatusCode::NOT_FOUND,
+            PokeApiClientError::TraslatableDescriptionNotFound(_)
+            | PokeApiClientError::EvolutionChainNotFound(_) => StatusCode::NOT_FOUND,
+            PokeApiClientError::UnexpectedResponse(_) => StatusCode::INTERNAL_SERVER_ERROR,
             PokeApiClientError::RequestError(e) => map_reqwest_to_actix_status_code(e.status()),
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self.api_error_response_body())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ApiError for PokeApiClientError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody {
         match self {
-            PokeApiClientError::TraslatableDescriptionNotFound(e) => {
-                HttpResponse::NotFound().json(ApiErrorResponseBody {
-                    code: ApiErrorResponseCode::TranslatableDescriptionNotFound,
-                    message: e.to_string(),
-                })
-            }
-            PokeApiClientError::RequestError(e) => HttpResponse::build(
-                map_reqwest_to_actix_status_code(e.status()),
-            )
-            .json(ApiErrorResponseBody {
+            PokeApiClientError::TraslatableDescriptionNotFound(e) => ApiErrorResponseBody {
+                code: ApiErrorResponseCode::TranslatableDescriptionNotFound,
+                message: e.to_string(),
+                limited_by: None,
+            },
+            PokeApiClientError::EvolutionChainNotFound(e) => ApiErrorResponseBody {
+                code: ApiErrorResponseCode::EvolutionChainNotFound,
+                message: e.to_string(),
+                limited_by: None,
+            },
+            PokeApiClientError::UnexpectedResponse(e) => ApiErrorResponseBody {
+                code: ApiErrorResponseCode::PokeApiError,
+                message: e.to_string(),
+                limited_by: None,
+            },
+            PokeApiClientError::RequestError(e) => ApiErrorResponseBody {
                 code: ApiErrorResponseCode::PokeApiError,
                 message: e.to_string(),
-            }),
+                limited_by: None,
+            },
         }
     }
 }
 
 /// Make `FunTranslationsClientError` an `actix_web` "citizen" by implementing `actix_web::error::ResponseError`.
+#[cfg(feature = "server")]
 impl ResponseError for FunTranslationsClientError {
     fn status_code(&self) -> StatusCode {
-        match self.0.status() {
+        match self.error.status() {
+            // A misconfigured secret is a server error, not to be confused with the client own authentication errors
+            Some(StatusCode::UNAUTHORIZED) => StatusCode::INTERNAL_SERVER_ERROR,
             Some(status_code) => map_reqwest_to_actix_status_code(Some(status_code)),
             None => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        match self.0.status() {
-            Some(StatusCode::TOO_MANY_REQUESTS) => HttpResponse::build(
-                map_reqwest_to_actix_status_code(Some(StatusCode::TOO_MANY_REQUESTS)),
-            )
-            .json(ApiErrorResponseBody {
+        HttpResponse::build(self.status_code()).json(self.api_error_response_body())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ApiError for FunTranslationsClientError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody {
+        match self.error.status() {
+            Some(StatusCode::TOO_MANY_REQUESTS) => ApiErrorResponseBody {
                 code: ApiErrorResponseCode::TooManyRequests,
-                message: self.0.to_string(),
-            }),
-            Some(status_code) => HttpResponse::build(map_reqwest_to_actix_status_code(Some(
-                status_code,
-            )))
-            .json(ApiErrorResponseBody {
-                code: ApiErrorResponseCode::FunTranslationsError,
-                message: self.0.to_string(),
-            }),
-            None => HttpResponse::InternalServerError().json(ApiErrorResponseBody {
+                message: self.to_string(),
+                limited_by: Some(RateLimitedBy::Upstream),
+            },
+            Some(StatusCode::UNAUTHORIZED) => ApiErrorResponseBody {
+                code: ApiErrorResponseCode::FunTranslationsUnauthorized,
+                message: self.to_string(),
+                limited_by: None,
+            },
+            _ => ApiErrorResponseBody {
                 code: ApiErrorResponseCode::FunTranslationsError,
-                message: self.0.to_string(),
-            }),
+                message: self.to_string(),
+                limited_by: None,
+            },
+        }
+    }
+}
+
+/// Make `DescriptionSourceError` an `actix_web` "citizen" by implementing `actix_web::error::ResponseError`.
+#[cfg(feature = "server")]
+impl ResponseError for DescriptionSourceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::PokeApi(e) => e.status_code(),
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self.api_error_response_body())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ApiError for DescriptionSourceError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody {
+        let code = match self {
+            Self::PokeApi(e) => return e.api_error_response_body(),
+            Self::NotFound(_) => ApiErrorResponseCode::TranslatableDescriptionNotFound,
+            Self::Other(_) => ApiErrorResponseCode::DescriptionSourceError,
+        };
+        ApiErrorResponseBody {
+            code,
+            message: self.to_string(),
+            limited_by: None,
+        }
+    }
+}
+
+/// Make `TranslatorError` an `actix_web` "citizen" by implementing `actix_web::error::ResponseError`.
+#[cfg(feature = "server")]
+impl ResponseError for TranslatorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::FunTranslations(e) => e.status_code(),
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self.api_error_response_body())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ApiError for TranslatorError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody {
+        let (code, limited_by) = match self {
+            Self::FunTranslations(e) => return e.api_error_response_body(),
+            Self::TooManyRequests(_) => (
+                ApiErrorResponseCode::TooManyRequests,
+                Some(RateLimitedBy::Upstream),
+            ),
+            Self::Other(_) => (ApiErrorResponseCode::TranslatorError, None),
+        };
+        ApiErrorResponseBody {
+            code,
+            message: self.to_string(),
+            limited_by,
+        }
+    }
+}
+
+/// Error of any of the steps needed to get a Pokémon "Shakespearean" description.
+#[derive(Debug)]
+pub enum ShakespeareanDescriptionError {
+    Source(DescriptionSourceError),
+    Translator(TranslatorError),
+}
+
+#[cfg(feature = "server")]
+impl ShakespeareanDescriptionError {
+    /// Whether the error is due to exceeded translator (e.g. FunTranslations API) limits.
+    pub fn is_too_many_requests(&self) -> bool {
+        self.status_code() == StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+impl StdError for ShakespeareanDescriptionError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Source(e) => Some(e),
+            Self::Translator(e) => Some(e),
+        }
+    }
+}
+
+impl Display for ShakespeareanDescriptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Source(e) => Display::fmt(e, f),
+            Self::Translator(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl From<DescriptionSourceError> for ShakespeareanDescriptionError {
+    fn from(error: DescriptionSourceError) -> Self {
+        Self::Source(error)
+    }
+}
+
+impl From<TranslatorError> for ShakespeareanDescriptionError {
+    fn from(error: TranslatorError) -> Self {
+        Self::Translator(error)
+    }
+}
+
+#[cfg(feature = "server")]
+impl ResponseError for ShakespeareanDescriptionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Source(e) => e.status_code(),
+            Self::Translator(e) => e.status_code(),
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self.api_error_response_body())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ApiError for ShakespeareanDescriptionError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody {
+        match self {
+            Self::Source(e) => e.api_error_response_body(),
+            Self::Translator(e) => e.api_error_response_body(),
+        }
+    }
+}
+
+/// Error of an API request that can't be served as it is, regardless of the upstreams.
+#[derive(Debug)]
+pub enum RequestError {
+    /// Invalid request (e.g. because of invalid parameters).
+    Invalid(String),
+    /// Request whose body exceeds the allowed size.
+    PayloadTooLarge(String),
+    /// Request accepting none of the available representations.
+    NotAcceptable(String),
+    /// Request without valid credentials (e.g. API key).
+    Unauthorized(String),
+    /// Request exceeding the allowed rate of its client.
+    TooManyRequests(String),
+    /// Request exceeding the daily quota of its client.
+    QuotaExceeded(String),
+}
+
+impl StdError for RequestError {}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Invalid(message)
+            | Self::PayloadTooLarge(message)
+            | Self::NotAcceptable(message)
+            | Self::Unauthorized(message)
+            | Self::TooManyRequests(message)
+            | Self::QuotaExceeded(message) => Display::fmt(message, f),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl ResponseError for RequestError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Invalid(_) => StatusCode::BAD_REQUEST,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::TooManyRequests(_) | Self::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(self.api_error_response_body())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ApiError for RequestError {
+    fn api_error_response_body(&self) -> ApiErrorResponseBody {
+        let code = match self {
+            Self::Invalid(_) => ApiErrorResponseCode::InvalidRequest,
+            Self::PayloadTooLarge(_) => ApiErrorResponseCode::PayloadTooLarge,
+            Self::NotAcceptable(_) => ApiErrorResponseCode::NotAcceptable,
+            Self::Unauthorized(_) => ApiErrorResponseCode::Unauthorized,
+            Self::TooManyRequests(_) => ApiErrorResponseCode::TooManyRequests,
+            Self::QuotaExceeded(_) => ApiErrorResponseCode::QuotaExceeded,
+        };
+        let limited_by = match self {
+            Self::TooManyRequests(_) | Self::QuotaExceeded(_) => Some(RateLimitedBy::Local),
+            _ => None,
+        };
+        ApiErrorResponseBody {
+            code,
+            message: self.to_string(),
+            limited_by,
         }
     }
 }
@@ -87,6 +363,7 @@ impl ResponseError for FunTranslationsClientError {
 /// With no input status code, returns a `actix_web::http::StatusCode::INTERNAL_SERVER_ERROR`.
 ///
 /// Panics if it can't get a valid `actix_web::http::StatusCode`.
+#[cfg(feature = "server")]
 fn map_reqwest_to_actix_status_code(reqwest_status_code: Option<ReqwestStatusCode>) -> StatusCode {
     reqwest_status_code.map(|s|
         StatusCode::from_u16(s.as_u16()).unwrap_or_else(|e| {
@@ -0,0 +1,120 @@
+use crate::poke_api_client::{EvolutionConditions, EvolutionStage};
+
+/// Tells an evolution chain in English prose, one sentence per evolving stage (e.g. "Bulbasaur evolves into Ivysaur
+/// at level 16. Ivysaur evolves into Venusaur at level 32."), to be translated like the Pokémon descriptions.
+pub fn narrate(chain: &EvolutionStage) -> String {
+    if chain.evolves_to.is_empty() {
+        return format!("{} does not evolve.", species_name(&chain.species));
+    }
+    let mut sentences = Vec::new();
+    narrate_stage(chain, &mut sentences);
+    sentences.join(" ")
+}
+
+fn narrate_stage(stage: &EvolutionStage, sentences: &mut Vec<String>) {
+    if stage.evolves_to.is_empty() {
+        return;
+    }
+    let evolutions = stage
+        .evolves_to
+        .iter()
+        .map(|next| {
+            let conditions = next
+                .conditions
+                .iter()
+                .map(narrate_conditions)
+                .filter(|conditions| !conditions.is_empty())
+                .collect::<Vec<_>>();
+            match conditions.is_empty() {
+                true => format!("into {}", species_name(&next.species)),
+                false => format!(
+                    "into {} {}",
+                    species_name(&next.species),
+                    conditions.join(" or ")
+                ),
+            }
+        })
+        .collect::<Vec<_>>();
+    sentences.push(format!(
+        "{} evolves {}.",
+        species_name(&stage.species),
+        enumerate_alternatives(&evolutions)
+    ));
+    for next in &stage.evolves_to {
+        narrate_stage(next, sentences);
+    }
+}
+
+fn narrate_conditions(conditions: &EvolutionConditions) -> String {
+    let mut phrases = Vec::new();
+    match conditions.trigger.as_deref() {
+        Some("use-item") => {
+            if let Some(item) = &conditions.item {
+                phrases.push(format!("using {}", with_article(&words(item))));
+            }
+        }
+        Some("trade") => phrases.push("when traded".to_string()),
+        Some("level-up") | None => {}
+        Some(trigger) => phrases.push(format!("by {}", words(trigger))),
+    }
+    if let Some(level) = conditions.min_level {
+        phrases.push(format!("at level {}", level));
+    }
+    if conditions.min_happiness.is_some() {
+        phrases.push("with high friendship".to_string());
+    }
+    if let Some(item) = &conditions.held_item {
+        phrases.push(format!("holding {}", with_article(&words(item))));
+    }
+    if let Some(known_move) = &conditions.known_move {
+        phrases.push(format!("knowing {}", words(known_move)));
+    }
+    if let Some(location) = &conditions.location {
+        phrases.push(format!("at {}", words(location)));
+    }
+    match conditions.time_of_day.as_deref() {
+        Some("day") => phrases.push("during the day".to_string()),
+        Some("night") => phrases.push("at night".to_string()),
+        Some(time_of_day) => phrases.push(format!("at {}", words(time_of_day))),
+        None => {}
+    }
+    if phrases.is_empty() && conditions.trigger.as_deref() == Some("level-up") {
+        phrases.push("by leveling up".to_string());
+    }
+    phrases.join(" ")
+}
+
+/// Joins alternatives as in "a, b or c".
+fn enumerate_alternatives(alternatives: &[String]) -> String {
+    match alternatives.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, others)) => format!("{} or {}", others.join(", "), last),
+        None => String::new(),
+    }
+}
+
+/// Capitalizes every part of a PokeApi species name (e.g. `mr-mime` as `Mr-Mime`).
+fn species_name(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Turns a PokeApi resource name into words (e.g. `thunder-stone` into `thunder stone`).
+fn words(name: &str) -> String {
+    name.replace('-', " ")
+}
+
+fn with_article(noun: &str) -> String {
+    match noun.chars().next() {
+        Some('a') | Some('e') | Some('i') | Some('o') | Some('u') => format!("an {}", noun),
+        _ => format!("a {}", noun),
+    }
+}
@@ -1,13 +1,85 @@
+use crate::cassettes::Cassettes;
+use crate::log_helpers::{get_discard_logger, log_upstream_call, Logger, UpstreamCall};
+use crate::telemetry::{in_client_span, trace_context_headers};
+use reqwest::header::HeaderValue;
 use reqwest::Error as ReqwestError;
 use reqwest::{Client, Url};
 use serde::Deserialize;
 use std::error::Error as StdError;
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::ops::Range;
 
 /// HTTP client to interact with FunTranslations API.
 #[derive(Clone)]
 pub struct FunTranslationsClient {
     pub endpoint: Url,
+    log: Logger,
+    max_chunk_chars: usize,
+    api_secret: Option<ApiSecret>,
+    cassettes: Option<Cassettes>,
+}
+
+/// Header carrying the secret of the FunTranslations API paid plans.
+pub const API_SECRET_HEADER: &str = "X-Funtranslations-Api-Secret";
+
+/// Secret of a FunTranslations API paid plan, redacted from its `Debug` representation so that it's never logged.
+#[derive(Clone)]
+pub struct ApiSecret(HeaderValue);
+
+impl ApiSecret {
+    /// Panics if the secret isn't a valid header value.
+    pub fn new(secret: &str) -> Self {
+        let mut header_value =
+            HeaderValue::from_str(secret.trim()).expect("Invalid FunTranslations API secret");
+        header_value.set_sensitive(true);
+        Self(header_value)
+    }
+
+    /// Reads the secret from `FUN_TRANSLATIONS_API_SECRET` or from the file at `FUN_TRANSLATIONS_API_SECRET_FILE`,
+    /// returning `None` if both are missing, meaning that the free plan is used.
+    ///
+    /// Panics in case of invalid secret or unreadable file.
+    pub fn from_env() -> Option<Self> {
+        if let Ok(secret) = std::env::var("FUN_TRANSLATIONS_API_SECRET") {
+            return Some(Self::new(&secret));
+        }
+        let path = std::env::var("FUN_TRANSLATIONS_API_SECRET_FILE").ok()?;
+        let secret = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "Can't read FunTranslations API secret file {}, error: {:?}",
+                path, e
+            )
+        });
+        Some(Self::new(&secret))
+    }
+}
+
+impl Debug for ApiSecret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("ApiSecret(<redacted>)")
+    }
+}
+
+/// FunTranslations API plan, determining its limits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FunTranslationsTier {
+    /// Free plan, limited to 5 requests per hour.
+    Free,
+    /// Paid plan, authenticated by an `ApiSecret`.
+    Paid,
+}
+
+impl FunTranslationsTier {
+    /// The tier of the configured `ApiSecret::from_env`, without reading it.
+    pub fn from_env() -> Self {
+        if std::env::var_os("FUN_TRANSLATIONS_API_SECRET").is_some()
+            || std::env::var_os("FUN_TRANSLATIONS_API_SECRET_FILE").is_some()
+        {
+            Self::Paid
+        } else {
+            Self::Free
+        }
+    }
 }
 
 impl FunTranslationsClient {
@@ -15,57 +87,258 @@ impl FunTranslationsClient {
         Self {
             endpoint: Url::parse(endpoint)
                 .unwrap_or_else(|e| panic!("Can't parse {} as URL, error: {:?}", endpoint, e)),
+            log: get_discard_logger(),
+            max_chunk_chars: DEFAULT_MAX_CHUNK_CHARS,
+            api_secret: None,
+            cassettes: None,
         }
     }
 
+    /// Sets the secret sent on every call to FunTranslations API, to use a paid plan.
+    pub fn with_api_secret(mut self, api_secret: ApiSecret) -> Self {
+        self.api_secret = Some(api_secret);
+        self
+    }
+
+    /// Sets the `Cassettes` recording or replaying every call made to FunTranslations API.
+    pub fn with_cassettes(mut self, cassettes: Cassettes) -> Self {
+        self.cassettes = Some(cassettes);
+        self
+    }
+
+    /// Sets the `Logger` used to log every call made to FunTranslations API.
+    pub fn with_logger(mut self, log: Logger) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Sets the max number of characters of the texts sent to FunTranslations API in a single call.
+    ///
+    /// Panics if `max_chunk_chars` is 0.
+    pub fn with_max_chunk_chars(mut self, max_chunk_chars: usize) -> Self {
+        assert!(max_chunk_chars > 0, "Invalid max chunk chars 0");
+        self.max_chunk_chars = max_chunk_chars;
+        self
+    }
+
     /// Given a text, gets the shakespearean translation by calling FunTranslation API.
     ///
     /// In case of errors, it transparently returns them.
     /// Note: the called FunTranslation API is throttled and returns an error and a status code of 429 in case of too
-    /// many requests (at the time of writing the limits are 5 requests per hour).
+    /// many requests (at the time of writing the limits are 5 requests per hour, lifted by paid plans), while it
+    /// returns a 401 in case of invalid `ApiSecret`.
     pub async fn translate(&self, text: &str) -> Result<String, FunTranslationsClientError> {
-        let api_url = format!("{}translate/shakespeare.json", self.endpoint);
+        self.translate_to(text, "shakespeare").await
+    }
+
+    /// Given a text and a FunTranslations style (e.g. `shakespeare` or `yoda`), gets the translation of the text in
+    /// that style by calling FunTranslation API.
+    ///
+    /// Texts up to the max chunk chars are translated with a single call, sending them as query parameter.
+    /// Longer texts are split on sentence boundaries (or, for longer sentences, on words) and their chunks are sent as
+    /// form bodies, not to hit the URL length limits, to be joined back in order. FunTranslations API translates a
+    /// single text per call, so each chunk costs a call of its limits: as two consecutive chunks together exceed the
+    /// max chunk chars, a text of `n` chars costs less than `2 * n / max_chunk_chars + 2` calls.
+    /// In case of errors, it transparently returns them, like `translate`, together with the failed chunk, if any.
+    pub async fn translate_to(
+        &self,
+        text: &str,
+        style: &str,
+    ) -> Result<String, FunTranslationsClientError> {
+        let chunks = split_in_chunks(text, self.max_chunk_chars);
+        if chunks.len() <= 1 {
+            return self
+                .translate_chunk(text, style, TextEncoding::Query)
+                .await
+                .map_err(FunTranslationsClientError::from);
+        }
+
+        let mut translations = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let translation = self
+                .translate_chunk(chunk, style, TextEncoding::Form)
+                .await
+                .map_err(|error| FunTranslationsClientError {
+                    error,
+                    chunk: Some(TextChunk {
+                        number: i + 1,
+                        count: chunks.len(),
+                    }),
+                })?;
+            translations.push(translation);
+        }
+        Ok(translations.join(" "))
+    }
+
+    /// Translates the given text with a single call to FunTranslation API.
+    async fn translate_chunk(
+        &self,
+        text: &str,
+        style: &str,
+        encoding: TextEncoding,
+    ) -> Result<String, ReqwestError> {
+        in_client_span("translate", async {
+            let api_url = format!("{}translate/{}.json", self.endpoint, style);
+
+            let (method, req) = match encoding {
+                TextEncoding::Query => {
+                    ("GET", Client::new().get(&api_url).query(&[("text", text)]))
+                }
+                TextEncoding::Form => {
+                    ("POST", Client::new().post(&api_url).form(&[("text", text)]))
+                }
+            };
+            let mut call = UpstreamCall::start(&self.log, method, &api_url);
+            let result = async {
+                let mut req = req.headers(trace_context_headers());
+                if let Some(ApiSecret(secret)) = &self.api_secret {
+                    req = req.header(API_SECRET_HEADER, secret.clone());
+                }
+                let resp = match &self.cassettes {
+                    Some(cassettes) => cassettes.send(req, &self.log).await?,
+                    None => req.send().await?,
+                };
+                call.record_response(&resp);
+                resp.error_for_status()?.json::<Translation>().await
+            }
+            .await;
+            log_upstream_call!(call, &result);
+
+            Ok(result?.contents.translated_text)
+        })
+        .await
+    }
+}
+
+/// How the text to translate is sent to FunTranslations API.
+#[derive(Clone, Copy)]
+enum TextEncoding {
+    /// As `text` query parameter of a GET request.
+    Query,
+    /// As `text` field of the form body of a POST request.
+    Form,
+}
 
-        let req = Client::new().get(&api_url).query(&[("text", text)]);
-        let resp = req.send().await;
+/// Default max number of characters of the texts sent to FunTranslations API in a single call.
+pub const DEFAULT_MAX_CHUNK_CHARS: usize = 500;
 
-        Ok(resp?
-            .error_for_status()?
-            .json::<ShakespeareanDescription>()
-            .await?
-            .contents
-            .translated_text)
+/// Splits the given text in chunks of at most `max_chars` characters, preferably on sentence boundaries.
+///
+/// Sentences longer than `max_chars` are split on whitespaces and words longer than `max_chars` wherever needed.
+/// Chunks are trimmed of their surrounding whitespaces.
+fn split_in_chunks(text: &str, max_chars: usize) -> Vec<&str> {
+    let chars_count = |range: &Range<usize>| text[range.clone()].chars().count();
+    if chars_count(&(0..text.len())) <= max_chars {
+        return vec![text];
     }
+
+    let pieces = split_after(text, 0..text.len(), |c, next| {
+        matches!(c, '.' | '!' | '?') && matches!(next, Some(next) if next.is_whitespace())
+    })
+    .into_iter()
+    .flat_map(|sentence| {
+        if chars_count(&sentence) <= max_chars {
+            return vec![sentence];
+        }
+        split_after(text, sentence, |c, _| c.is_whitespace())
+            .into_iter()
+            .flat_map(|word| {
+                if chars_count(&word) <= max_chars {
+                    return vec![word];
+                }
+                let mut word_chars = 0;
+                split_after(text, word, |_, _| {
+                    word_chars += 1;
+                    word_chars % max_chars == 0
+                })
+            })
+            .collect()
+    });
+
+    let mut chunks: Vec<Range<usize>> = Vec::new();
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(chunk) if chars_count(&(chunk.start..piece.end)) <= max_chars => {
+                chunk.end = piece.end
+            }
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+        .into_iter()
+        .map(|chunk| text[chunk].trim())
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+/// Splits the given range of the text in contiguous ranges, each ending right after a char for which `is_boundary`
+/// (called with the char and the following one, if any) returns `true`.
+fn split_after(
+    text: &str,
+    range: Range<usize>,
+    mut is_boundary: impl FnMut(char, Option<char>) -> bool,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = range.start;
+    let mut chars = text[range.clone()].char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if is_boundary(c, chars.peek().map(|&(_, next)| next)) {
+            let end = range.start + i + c.len_utf8();
+            ranges.push(start..end);
+            start = end;
+        }
+    }
+    if start < range.end {
+        ranges.push(start..range.end);
+    }
+    ranges
 }
 
 #[derive(Debug)]
-pub struct FunTranslationsClientError(pub ReqwestError);
+pub struct FunTranslationsClientError {
+    pub error: ReqwestError,
+    /// Failed chunk of a text translated in chunks.
+    pub chunk: Option<TextChunk>,
+}
+
+/// Position of a chunk among the ones a text is split in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextChunk {
+    /// 1-based position of the chunk.
+    pub number: usize,
+    pub count: usize,
+}
 
 impl StdError for FunTranslationsClientError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
-        Some(&self.0)
+        Some(&self.error)
     }
 }
 
 impl Display for FunTranslationsClientError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        Display::fmt(&self.0, f)
+        match self.chunk {
+            Some(TextChunk { number, count }) => {
+                write!(f, "chunk {} of {}: {}", number, count, self.error)
+            }
+            None => Display::fmt(&self.error, f),
+        }
     }
 }
 
 impl From<ReqwestError> for FunTranslationsClientError {
     fn from(error: ReqwestError) -> Self {
-        FunTranslationsClientError(error)
+        FunTranslationsClientError { error, chunk: None }
     }
 }
 
 #[derive(Debug, Deserialize)]
-struct ShakespeareanDescription {
-    contents: ShakespeareanDescriptionContents,
+struct Translation {
+    contents: TranslationContents,
 }
 
 #[derive(Debug, Deserialize)]
-struct ShakespeareanDescriptionContents {
+struct TranslationContents {
     #[serde(rename = "translated")]
     translated_text: String,
 }
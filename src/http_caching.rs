@@ -0,0 +1,50 @@
+use crate::env_helpers::parse_env_var;
+use actix_web::http::header::{HeaderMap, HeaderValue, IF_NONE_MATCH};
+use sha2::{Digest, Sha256};
+
+/// `Cache-Control` header values of the cacheable API services.
+#[derive(Clone)]
+pub struct CacheControlConfig {
+    /// Of the `get_shakespearean_description` API service.
+    pub description: HeaderValue,
+    /// Of the `get_openapi_spec` API service.
+    pub openapi_spec: HeaderValue,
+    /// Of the `get_docs` API service.
+    pub docs: HeaderValue,
+}
+
+impl CacheControlConfig {
+    /// Reads the header values from `POKESPEARE_CACHE_CONTROL_DESCRIPTION` (`public, max-age=3600` by default),
+    /// `POKESPEARE_CACHE_CONTROL_OPENAPI_SPEC` and `POKESPEARE_CACHE_CONTROL_DOCS` (both `public, max-age=300` by
+    /// default).
+    ///
+    /// Panics in case of invalid env vars (e.g. not valid header values).
+    pub fn from_env() -> Self {
+        Self {
+            description: parse_env_var("POKESPEARE_CACHE_CONTROL_DESCRIPTION")
+                .unwrap_or_else(|| HeaderValue::from_static("public, max-age=3600")),
+            openapi_spec: parse_env_var("POKESPEARE_CACHE_CONTROL_OPENAPI_SPEC")
+                .unwrap_or_else(|| HeaderValue::from_static("public, max-age=300")),
+            docs: parse_env_var("POKESPEARE_CACHE_CONTROL_DOCS")
+                .unwrap_or_else(|| HeaderValue::from_static("public, max-age=300")),
+        }
+    }
+}
+
+/// Returns the strong `ETag` header value of a response with the given body, derived from its SHA-256 digest.
+pub fn strong_etag(body: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(body))
+}
+
+/// Whether the request `If-None-Match` header matches the given `ETag` header value, meaning that the client already
+/// has the response and a `304 Not Modified` can be returned instead.
+///
+/// As per RFC 7232, `If-None-Match` uses the weak comparison: `W/` prefixes are ignored.
+pub fn is_not_modified(request_headers: &HeaderMap, etag: &str) -> bool {
+    request_headers
+        .get_all(IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag.trim_start_matches("W/"))
+}
@@ -1,5 +1,30 @@
+pub mod api_keys;
+pub mod cache_helpers;
+pub mod cassettes;
+pub mod content_negotiation;
+#[cfg(feature = "server")]
+pub mod cors;
+pub mod descriptions_cache;
+pub mod env_helpers;
 pub mod errors;
+pub mod evolution_narratives;
 pub mod fun_translations_client;
+#[cfg(feature = "server")]
+pub mod http_caching;
+pub mod log_drains;
+pub mod log_helpers;
+#[cfg(feature = "server")]
+pub mod openapi;
 pub mod poke_api_client;
+pub mod rate_limiting;
+#[cfg(feature = "server")]
 pub mod services;
 pub mod services_api_models;
+#[cfg(feature = "server")]
+pub mod shutdown;
+pub mod telemetry;
+#[cfg(feature = "server")]
+pub mod tls;
+#[cfg(feature = "server")]
+pub mod upstream_stub;
+pub mod upstreams;
@@ -0,0 +1,137 @@
+use slog::{Drain, Key, OwnedKVList, Record, Serializer, KV};
+use std::fmt::{Arguments, Write as FmtWrite};
+use std::fs::{File, OpenOptions};
+use std::io::{Result as IoResult, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// `slog::Drain` writing records in [logfmt](https://brandur.org/logfmt) format, one per line.
+///
+/// Each line starts with `ts`, `level` and `msg`, followed by the record and logger key-values.
+pub struct Logfmt<W: Write> {
+    io: Mutex<W>,
+}
+
+impl<W: Write> Logfmt<W> {
+    pub fn new(io: W) -> Self {
+        Self { io: Mutex::new(io) }
+    }
+}
+
+impl<W: Write> Drain for Logfmt<W> {
+    type Ok = ();
+    type Err = std::io::Error;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> IoResult<()> {
+        let mut line = LogfmtSerializer(String::new());
+        line.push("ts", &chrono::Local::now().to_rfc3339());
+        line.push("level", record.level().as_short_str());
+        line.push("msg", &record.msg().to_string());
+        record.kv().serialize(record, &mut line)?;
+        values.serialize(record, &mut line)?;
+
+        let mut io = self.io.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(io, "{}", line.0)?;
+        io.flush()
+    }
+}
+
+struct LogfmtSerializer(String);
+
+impl LogfmtSerializer {
+    fn push(&mut self, key: &str, value: &str) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        self.0.push_str(key);
+        self.0.push('=');
+        if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            self.0.push_str(value);
+        }
+    }
+}
+
+impl Serializer for LogfmtSerializer {
+    fn emit_arguments(&mut self, key: Key, val: &Arguments) -> slog::Result {
+        self.push(key, &val.to_string());
+        Ok(())
+    }
+}
+
+/// File writer that rotates the written file once it reaches `max_bytes`.
+///
+/// On rotation `<path>` is renamed to `<path>.1`, `<path>.1` to `<path>.2` and so on, keeping at most `max_files`
+/// rotated files.
+/// Rotation only happens at the start of a new line so that a single log record never spans two files.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written_bytes: u64,
+    at_line_start: bool,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_bytes: u64, max_files: usize) -> IoResult<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written_bytes = file.metadata()?.len();
+        Ok(Self {
+            path: path.into(),
+            max_bytes,
+            max_files,
+            file,
+            written_bytes,
+            at_line_start: true,
+        })
+    }
+
+    fn rotate(&mut self) -> IoResult<()> {
+        self.file.flush()?;
+        for n in (1..self.max_files).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(n + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.written_bytes = 0;
+        Ok(())
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        path.into()
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        if self.at_line_start
+            && self.written_bytes > 0
+            && self.written_bytes + buf.len() as u64 > self.max_bytes
+        {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written_bytes += written as u64;
+        if written > 0 {
+            self.at_line_start = buf[written - 1] == b'\n';
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        self.file.flush()
+    }
+}
@@ -1,17 +1,228 @@
+use crate::env_helpers::parse_env_var;
+use crate::log_drains::{Logfmt, RotatingFile};
+use crate::telemetry::set_current_span_attribute;
+use opentelemetry::KeyValue;
+use reqwest::{Response, Url};
 use slog::Drain;
 pub use slog::{debug, error, info, o, trace, warn};
 pub use slog::{FnValue, Logger};
+use slog_async::{Async, AsyncGuard};
 use slog_json::Json;
+use slog_term::{CompactFormat, PlainDecorator};
+use std::error::Error as StdError;
+use std::io::Write;
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::Instant;
 
-pub fn get_root_logger() -> Logger {
-    Logger::root(
-        Mutex::new(slog_envlogger::new(Json::default(std::io::stdout()))).map(slog::Fuse),
+/// Format of the emitted log records.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    Json,
+    Compact,
+    Logfmt,
+}
+
+/// Destination of the emitted log records.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogDestination {
+    Stdout,
+    Stderr,
+    File {
+        path: PathBuf,
+        max_bytes: u64,
+        max_files: usize,
+    },
+}
+
+/// Root `Logger` configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    pub destination: LogDestination,
+    /// `slog_envlogger` filter directives (e.g. `info,pokespeare::poke_api_client=debug`).
+    pub filter: String,
+}
+
+impl LogConfig {
+    /// Builds the configuration from the following env vars:
+    /// - `POKESPEARE_LOG_FORMAT`: `json` (default), `compact` or `logfmt`
+    /// - `POKESPEARE_LOG_DESTINATION`: `stdout` (default), `stderr` or `file`
+    /// - `POKESPEARE_LOG_FILE`: path of the log file, required with `file` destination
+    /// - `POKESPEARE_LOG_FILE_MAX_BYTES`: size triggering the log file rotation, 10 MiB by default
+    /// - `POKESPEARE_LOG_FILE_MAX_FILES`: number of rotated log files to keep, 5 by default
+    /// - `RUST_LOG`: filter directives, `info` by default
+    ///
+    /// Panics in case of invalid env vars or missing `POKESPEARE_LOG_FILE` with `file` destination.
+    pub fn from_env() -> Self {
+        let format = match std::env::var("POKESPEARE_LOG_FORMAT").as_deref() {
+            Err(_) | Ok("json") => LogFormat::Json,
+            Ok("compact") => LogFormat::Compact,
+            Ok("logfmt") => LogFormat::Logfmt,
+            Ok(other) => panic!("Invalid POKESPEARE_LOG_FORMAT {:?}", other),
+        };
+        let destination = match std::env::var("POKESPEARE_LOG_DESTINATION").as_deref() {
+            Err(_) | Ok("stdout") => LogDestination::Stdout,
+            Ok("stderr") => LogDestination::Stderr,
+            Ok("file") => LogDestination::File {
+                path: std::env::var("POKESPEARE_LOG_FILE")
+                    .expect("Missing required POKESPEARE_LOG_FILE")
+                    .into(),
+                max_bytes: parse_env_var("POKESPEARE_LOG_FILE_MAX_BYTES")
+                    .unwrap_or(10 * 1024 * 1024),
+                max_files: parse_env_var("POKESPEARE_LOG_FILE_MAX_FILES").unwrap_or(5),
+            },
+            Ok(other) => panic!("Invalid POKESPEARE_LOG_DESTINATION {:?}", other),
+        };
+        Self {
+            format,
+            destination,
+            filter: std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+        }
+    }
+}
+
+/// Builds the root `Logger` according to the given configuration.
+///
+/// Records are filtered by `LogConfig::filter` and written asynchronously: the returned `AsyncGuard` flushes the
+/// pending ones when dropped, after which further records are lost.
+///
+/// Panics if the log file can't be opened.
+pub fn get_root_logger(config: &LogConfig) -> (Logger, AsyncGuard) {
+    let io: Box<dyn Write + Send> = match &config.destination {
+        LogDestination::Stdout => Box::new(std::io::stdout()),
+        LogDestination::Stderr => Box::new(std::io::stderr()),
+        LogDestination::File {
+            path,
+            max_bytes,
+            max_files,
+        } => Box::new(
+            RotatingFile::open(path, *max_bytes, *max_files)
+                .unwrap_or_else(|e| panic!("Can't open log file {:?}, error: {:?}", path, e)),
+        ),
+    };
+    let (drain, guard) = match config.format {
+        LogFormat::Json => Async::new(Json::default(io).fuse()).build_with_guard(),
+        LogFormat::Compact => {
+            Async::new(CompactFormat::new(PlainDecorator::new(io)).build().fuse())
+                .build_with_guard()
+        }
+        LogFormat::Logfmt => Async::new(Logfmt::new(io).fuse()).build_with_guard(),
+    };
+    let drain = slog_envlogger::LogBuilder::new(drain.fuse())
+        .parse(&config.filter)
+        .build();
+
+    let log = Logger::root(
+        Mutex::new(drain).map(slog::Fuse),
         o!(
             "file" => FnValue(move |info| info.file()),
             "module" => FnValue(move |info| info.module()),
             "function" => FnValue(move |info| info.function()),
             "line" => FnValue(move |info| format!("{}", info.line())),
         ),
-    )
+    );
+    (log, guard)
+}
+
+/// Returns a `Logger` that drops every record, used as default by the HTTP clients.
+pub fn get_discard_logger() -> Logger {
+    Logger::root(slog::Discard, o!())
+}
+
+/// Collects the `Display` representation of the given error and of all its `source()`s, outermost first and
+/// without consecutive duplicates.
+pub fn error_chain(error: &(dyn StdError + 'static)) -> Vec<String> {
+    std::iter::successors(Some(error), |&e| e.source())
+        .map(ToString::to_string)
+        .fold(Vec::new(), |mut chain, e| {
+            // Wrapping errors often display the same message as their source
+            if chain.last() != Some(&e) {
+                chain.push(e);
+            }
+            chain
+        })
+}
+
+/// Tracks a single call to an upstream service, to be logged with `log_upstream_call!` once finished.
+///
+/// The logged URL is stripped of its query string and credentials to avoid leaking texts or secrets.
+pub struct UpstreamCall<'a> {
+    pub(crate) log: &'a Logger,
+    pub(crate) method: &'static str,
+    pub(crate) url: String,
+    pub(crate) started_at: Instant,
+    pub(crate) status: Option<u16>,
+    pub(crate) bytes: Option<u64>,
+}
+
+impl<'a> UpstreamCall<'a> {
+    /// Starts tracking the call, recording its method and URL also on the current span.
+    pub fn start(log: &'a Logger, method: &'static str, url: &str) -> Self {
+        let url = loggable_url(url);
+        set_current_span_attribute(KeyValue::new("http.method", method));
+        set_current_span_attribute(KeyValue::new("http.url", url.clone()));
+        Self {
+            log,
+            method,
+            url,
+            started_at: Instant::now(),
+            status: None,
+            bytes: None,
+        }
+    }
+
+    /// Records status code and size of the upstream response, recording the status code also on the current span.
+    pub fn record_response(&mut self, resp: &Response) {
+        self.status = Some(resp.status().as_u16());
+        set_current_span_attribute(KeyValue::new(
+            "http.status_code",
+            i64::from(resp.status().as_u16()),
+        ));
+        self.bytes = resp.content_length();
+    }
+}
+
+/// Logs an `UpstreamCall` with its latency and the outcome of the given `Result`: `info` on success and `warn` on
+/// failure.
+///
+/// It's a macro so that records carry the module of the calling client, making them filterable per module.
+macro_rules! log_upstream_call {
+    ($call:expr, $result:expr) => {{
+        let call: $crate::log_helpers::UpstreamCall = $call;
+        let latency_ms = call.started_at.elapsed().as_millis() as u64;
+        match $result {
+            Ok(_) => $crate::log_helpers::info!(call.log, "Upstream call succeeded";
+                "method" => call.method,
+                "url" => &call.url,
+                "status" => call.status,
+                "bytes" => call.bytes,
+                "latency_ms" => latency_ms,
+                "outcome" => "ok",
+            ),
+            Err(e) => $crate::log_helpers::warn!(call.log, "Upstream call failed";
+                "method" => call.method,
+                "url" => &call.url,
+                "status" => call.status,
+                "bytes" => call.bytes,
+                "latency_ms" => latency_ms,
+                "outcome" => "error",
+                "error_chain" => ?$crate::log_helpers::error_chain(e),
+            ),
+        }
+    }};
+}
+pub(crate) use log_upstream_call;
+
+fn loggable_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut url) => {
+            url.set_query(None);
+            url.set_fragment(None);
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.to_string()
+        }
+        Err(_) => "<invalid url>".into(),
+    }
 }
@@ -1,32 +1,115 @@
-mod errors;
-mod fun_translations_client;
-mod log_helpers;
-mod poke_api_client;
-mod services;
-mod services_api_models;
-
 use actix_slog::StructuredLogger;
-use actix_web::middleware::Compress;
+use actix_web::dev::Server;
+use actix_web::middleware::{Compress, Condition};
+use actix_web::rt::System;
+use actix_web::web::Data;
 use actix_web::{App, HttpServer};
-use log_helpers::*;
+use pokespeare::cors::CorsConfig;
+use pokespeare::log_helpers::*;
+use pokespeare::services::{self, SharedState};
+use pokespeare::shutdown::{shutdown_signal, Readiness, ShutdownConfig};
+use pokespeare::telemetry;
+use pokespeare::tls::TlsConfig;
+
+fn main() -> std::io::Result<()> {
+    let (log, log_guard) = get_root_logger(&LogConfig::from_env());
+    // Must outlive the actix System and be dropped outside of it to flush the pending spans
+    let _tracing = telemetry::init_tracing_from_env();
+
+    // The plain listener is optional only when the TLS one is configured
+    let listen_addr = std::env::var("POKESPEARE_LISTEN_ADDR").ok();
+    let tls_config = TlsConfig::from_env();
+    if listen_addr.is_none() && tls_config.is_none() {
+        panic!("Missing required POKESPEARE_LISTEN_ADDR or POKESPEARE_TLS_LISTEN_ADDR");
+    }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    let log = get_root_logger();
-    std::env::set_var("RUST_LOG", "actix_web=info");
+    // Shared by the App instances of all the server workers
+    let shared_state = SharedState::from_env();
+    let cors_config = CorsConfig::from_env();
+    let shutdown_config = ShutdownConfig::from_env();
 
-    let listen_addr =
-        std::env::var("POKESPEARE_LISTEN_ADDR").expect("Missing required POKESPEARE_LISTEN_ADDR");
+    match shared_state.cache.restore() {
+        Ok(Some(entries)) => info!(log, "Restored descriptions cache"; "entries" => entries),
+        Ok(None) => {}
+        Err(e) => warn!(log, "Can't restore descriptions cache"; "error" => %e),
+    }
+
+    let (server_log, server_shared_state) = (log.clone(), shared_state.clone());
+    let result = System::new("pokespeare").block_on(async move {
+        let (log, shared_state) = (server_log, server_shared_state);
+        info!(log, "Start server";
+            "listen_addr" => ?listen_addr,
+            "tls_listen_addr" => ?tls_config.as_ref().map(|tls| &tls.listen_addr),
+        );
+        let (tls_log, shutdown_log) = (log.clone(), log.clone());
+        let readiness = shared_state.readiness.clone();
+        let mut server = HttpServer::new(move || {
+            App::new()
+                .wrap(Condition::new(
+                    cors_config.is_some(),
+                    cors_config
+                        .as_ref()
+                        .map(CorsConfig::cors)
+                        .unwrap_or_default(),
+                ))
+                .wrap(Compress::default())
+                .wrap(StructuredLogger::new(log.clone()))
+                .configure(|cfg| services::config_app(cfg, &log, &shared_state))
+        })
+        // Signals are handled by stop_on_signal, failing readiness before stopping the server
+        .disable_signals()
+        .shutdown_timeout(shutdown_config.drain_timeout.as_secs());
+        if let Some(listen_addr) = listen_addr {
+            server = server.bind(listen_addr)?;
+        }
+        if let Some(tls_config) = tls_config {
+            let server_config = tls_config
+                .server_config(&tls_log)
+                .unwrap_or_else(|e| panic!("Invalid TLS configuration, error: {}", e));
+            server = server.bind_rustls(tls_config.listen_addr, server_config)?;
+        }
+        let server = server.run();
+        actix_rt::spawn(stop_on_signal(
+            server.clone(),
+            readiness,
+            shutdown_config,
+            shutdown_log,
+        ));
+        server.await
+    });
+
+    match shared_state.cache.persist() {
+        Ok(Some(entries)) => info!(log, "Persisted descriptions cache"; "entries" => entries),
+        Ok(None) => {}
+        Err(e) => error!(log, "Can't persist descriptions cache"; "error" => %e),
+    }
+    info!(log, "Server stopped");
+    // Flushes the pending log records, as the Loggers of the server workers could outlive it
+    drop(log_guard);
+    result
+}
 
-    info!(log, "Start server"; "listen_addr" => ?listen_addr);
-    HttpServer::new(move || {
-        App::new()
-            .wrap(Compress::default())
-            .wrap(StructuredLogger::new(log.clone()))
-            .data(log.clone())
-            .configure(services::config_app)
-    })
-    .bind(listen_addr)?
-    .run()
-    .await
+/// Gracefully stops the server on `SIGTERM` or `SIGINT`: readiness is failed first, so that no new requests are routed
+/// to the server, and then in-flight requests are drained within `ShutdownConfig::drain_timeout`.
+async fn stop_on_signal(
+    server: Server,
+    readiness: Data<Readiness>,
+    config: ShutdownConfig,
+    log: Logger,
+) {
+    let signal = match shutdown_signal().await {
+        Ok(signal) => signal,
+        Err(e) => {
+            error!(log, "Can't listen to shutdown signals"; "error" => %e);
+            return;
+        }
+    };
+    info!(log, "Start shutdown, failing readiness";
+        "signal" => signal,
+        "readiness_delay_secs" => config.readiness_delay.as_secs(),
+    );
+    readiness.fail();
+    actix_rt::time::delay_for(config.readiness_delay).await;
+    info!(log, "Drain in-flight requests"; "timeout_secs" => config.drain_timeout.as_secs());
+    server.stop(true).await;
 }
@@ -0,0 +1,118 @@
+use crate::api_keys::API_KEY_HEADER;
+use crate::errors::{ApiErrorResponseBody, ApiErrorResponseCode, RateLimitedBy};
+use crate::services;
+use crate::services_api_models::{
+    EvolutionApiResponse, EvolutionConditionsApiResponse, EvolutionStageApiResponse,
+    ShakespeareanDescriptionApiResponse, ShakespeareanDescriptionsBatchApiItem,
+    ShakespeareanDescriptionsBatchApiRequest, ShakespeareanDescriptionsBatchApiResponse,
+    ShakespeareanDescriptionsBatchApiResult, SpeciesApiResponse, TranslationApiRequest,
+    TranslationApiResponse,
+};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityRequirement, SecurityScheme};
+use utoipa::openapi::{
+    Components, ContentBuilder, Deprecated, OpenApi as OpenApiSpec, Ref, ResponseBuilder,
+};
+use utoipa::{Modify, OpenApi};
+
+/// OpenAPI 3 specification of the API services, generated from their annotations and models.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "pokespeare",
+        description = "What if Pokémon were described by William Shakespeare?"
+    ),
+    paths(
+        services::get_shakespearean_description,
+        services::get_shakespearean_evolution,
+        services::get_shakespearean_descriptions_batch,
+        services::translate_text
+    ),
+    modifiers(&ApiKeySecurity, &UnversionedAliases),
+    components(schemas(
+        ShakespeareanDescriptionApiResponse,
+        SpeciesApiResponse,
+        EvolutionApiResponse,
+        EvolutionStageApiResponse,
+        EvolutionConditionsApiResponse,
+        ShakespeareanDescriptionsBatchApiRequest,
+        ShakespeareanDescriptionsBatchApiResponse,
+        ShakespeareanDescriptionsBatchApiItem,
+        ShakespeareanDescriptionsBatchApiResult,
+        TranslationApiRequest,
+        TranslationApiResponse,
+        ApiErrorResponseBody,
+        ApiErrorResponseCode,
+        RateLimitedBy
+    ))
+)]
+pub struct ApiDoc;
+
+/// Documents the API key authentication of the API services, optional as it's enabled only when API keys are
+/// configured.
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        openapi
+            .components
+            .get_or_insert_with(Components::new)
+            .add_security_scheme(
+                "api_key",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                    API_KEY_HEADER,
+                    "API key of the client, required only if API keys are configured",
+                ))),
+            );
+
+        let unauthorized = ResponseBuilder::new()
+            .description("Missing or unknown API key (`UNAUTHORIZED`)")
+            .content(
+                "application/json",
+                ContentBuilder::new()
+                    .schema(Ref::from_schema_name("ApiErrorResponseBody"))
+                    .build(),
+            )
+            .build();
+        for operation in openapi
+            .paths
+            .paths
+            .values_mut()
+            .flat_map(|item| item.operations.values_mut())
+        {
+            operation.security = Some(vec![
+                SecurityRequirement::default(),
+                SecurityRequirement::new("api_key", Vec::<String>::new()),
+            ]);
+            operation
+                .responses
+                .responses
+                .insert("401".into(), unauthorized.clone().into());
+        }
+    }
+}
+
+/// Documents the deprecated unversioned aliases of the version 1 API services.
+struct UnversionedAliases;
+
+impl Modify for UnversionedAliases {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        let aliases = openapi
+            .paths
+            .paths
+            .iter()
+            .filter_map(|(path, item)| {
+                let alias = path.strip_prefix("/v1")?;
+                let mut item = item.clone();
+                for operation in item.operations.values_mut() {
+                    operation.deprecated = Some(Deprecated::True);
+                    operation.operation_id = operation
+                        .operation_id
+                        .as_ref()
+                        .map(|id| format!("{}_unversioned", id));
+                }
+                Some((alias.to_string(), item))
+            })
+            .collect::<Vec<_>>();
+        openapi.paths.paths.extend(aliases);
+    }
+}
@@ -1,14 +1,29 @@
+use crate::cache_helpers::evict_least_recently_used;
+use crate::cassettes::Cassettes;
+use crate::env_helpers::parse_env_var;
+use crate::log_helpers::{
+    error_chain, get_discard_logger, log_upstream_call, warn, Logger, UpstreamCall,
+};
+use crate::telemetry::{in_client_span, trace_context_headers};
 use rand::prelude::*;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use reqwest::Error as ReqwestError;
-use reqwest::Url;
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// HTTP client to interact with PokeApi API.
 #[derive(Clone)]
 pub struct PokeApiClient {
     endpoint: Url,
+    log: Logger,
+    cassettes: Option<Cassettes>,
+    species_cache: Option<Arc<SpeciesCache>>,
 }
 
 impl PokeApiClient {
@@ -16,9 +31,30 @@ impl PokeApiClient {
         Self {
             endpoint: Url::parse(endpoint)
                 .unwrap_or_else(|e| panic!("Can't parse {} as URL, error: {:?}", endpoint, e)),
+            log: get_discard_logger(),
+            cassettes: None,
+            species_cache: None,
         }
     }
 
+    /// Sets the `Cassettes` recording or replaying every call made to PokeApi API.
+    pub fn with_cassettes(mut self, cassettes: Cassettes) -> Self {
+        self.cassettes = Some(cassettes);
+        self
+    }
+
+    /// Sets the `SpeciesCache` of the species got from PokeApi API, possibly shared with other clients.
+    pub fn with_species_cache(mut self, species_cache: Arc<SpeciesCache>) -> Self {
+        self.species_cache = Some(species_cache);
+        self
+    }
+
+    /// Sets the `Logger` used to log every call made to PokeApi API.
+    pub fn with_logger(mut self, log: Logger) -> Self {
+        self.log = log;
+        self
+    }
+
     /// Given a Pokémon name, gets one of its English description randomly.
     ///
     /// In case of no available English descriptions, returns `Err(DescriptionNotFound)`.
@@ -29,25 +65,229 @@ impl PokeApiClient {
         &self,
         pokemon_name: &str,
     ) -> Result<String, PokeApiClientError> {
+        self.get_random_description_with_metadata(pokemon_name)
+            .await
+            .map(|(description, _)| description)
+    }
+
+    /// Like `get_random_description`, but returning also the `SpeciesMetadata` of the Pokémon, found in the same
+    /// PokeApi API response.
+    pub async fn get_random_description_with_metadata(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<(String, SpeciesMetadata), PokeApiClientError> {
+        in_client_span("get_random_description", async {
+            let (api_url, species) = self.get_species(pokemon_name).await?;
+
+            let language_filter = "en";
+            let description = Self::pick_random_description(&species.descriptions, language_filter)
+                .ok_or_else(|| {
+                    PokeApiClientError::TraslatableDescriptionNotFound(DescriptionNotFound {
+                        api_url,
+                        language_filter: language_filter.into(),
+                    })
+                })?
+                .text
+                .as_str();
+            Ok((
+                Self::cleanup_description(description),
+                SpeciesMetadata::from(&species),
+            ))
+        })
+        .await
+    }
+
+    /// Given a Pokémon name, gets the evolution chain its species belongs to, starting from its first stage.
+    ///
+    /// The chain is requested to the configured endpoint rather than to the host of the species `evolution_chain` URL,
+    /// so that stand-ins and recordings of PokeApi API serve it too.
+    /// In case of species without evolution chain, returns `Err(EvolutionChainNotFound)`.
+    pub async fn get_evolution_chain(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<EvolutionStage, PokeApiClientError> {
+        in_client_span("get_evolution_chain", async {
+            let (api_url, species) = self.get_species(pokemon_name).await?;
+            let chain_id = species
+                .evolution_chain
+                .as_ref()
+                .and_then(|chain| chain.url.trim_end_matches('/').rsplit('/').next())
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| {
+                    PokeApiClientError::EvolutionChainNotFound(EvolutionChainNotFound { api_url })
+                })?;
+
+            let api_url = format!("{}api/v2/evolution-chain/{}/", self.endpoint, chain_id);
+            let chain = self.get_json::<EvolutionChain>(&api_url).await?;
+            Ok(EvolutionStage {
+                stale: species.stale,
+                ..EvolutionStage::from(&chain.chain)
+            })
+        })
+        .await
+    }
+
+    /// Gets the species of the given Pokémon, returning also the PokeApi URL it was got from.
+    ///
+    /// With a `SpeciesCache`, species are served from it until their TTL expires and then revalidated with a
+    /// conditional request. If PokeApi API is unavailable (i.e. unreachable or failing with a server error or a
+    /// `429 Too Many Requests`) an expired species is served anyway, marked as stale.
+    async fn get_species(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<(String, PokemonSpecies), PokeApiClientError> {
+        let cache = match &self.species_cache {
+            Some(cache) => cache,
+            None => {
+                let (api_url, species, _) = self.fetch_species(pokemon_name).await?;
+                return Ok((api_url, species));
+            }
+        };
+        let cached = match cache.get(pokemon_name) {
+            Some(cached) if !cached.is_expired(cache.ttl) => {
+                return Ok((cached.api_url, cached.species));
+            }
+            Some(cached) => cached,
+            None => {
+                let (api_url, species, validators) = self.fetch_species(pokemon_name).await?;
+                cache.insert(pokemon_name, &api_url, &species, validators);
+                return Ok((api_url, species));
+            }
+        };
+
+        let revalidation = self
+            .get_json_if_modified::<PokemonSpecies>(&cached.api_url, &cached.validators)
+            .await;
+        match revalidation {
+            Ok(Fetched::NotModified) => {
+                cache.insert(
+                    pokemon_name,
+                    &cached.api_url,
+                    &cached.species,
+                    cached.validators,
+                );
+                Ok((cached.api_url, cached.species))
+            }
+            Ok(Fetched::Modified(mut species, validators)) => {
+                species.name = species.name.or(cached.species.name);
+                cache.insert(pokemon_name, &cached.api_url, &species, validators);
+                Ok((cached.api_url, species))
+            }
+            Err(e) if is_unavailable(&e) => {
+                warn!(self.log, "Serving stale species, PokeApi API is unavailable";
+                    "pokemon_name" => pokemon_name,
+                    "url" => &cached.api_url,
+                    "age_secs" => cached.fetched_at.elapsed().as_secs(),
+                    "error_chain" => ?error_chain(&e),
+                );
+                let mut species = cached.species;
+                species.stale = true;
+                Ok((cached.api_url, species))
+            }
+            Err(e) => {
+                cache.remove(pokemon_name, &cached.species);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Fetches the species of the given Pokémon, returning also the PokeApi URL it was got from and its validators.
+    ///
+    /// Names of Pokémon forms and varieties (e.g. `charizard-mega-x`) aren't species ones: when PokeApi API knows no
+    /// species with the given name, the name is resolved to its species through the `pokemon` resource. If that fails
+    /// too, the error of the species lookup is returned.
+    async fn fetch_species(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<(String, PokemonSpecies, Validators), PokeApiClientError> {
         let api_url = format!("{}api/v2/pokemon-species/{}", self.endpoint, pokemon_name);
+        let species_error = match self
+            .get_json_with_validators::<PokemonSpecies>(&api_url)
+            .await
+        {
+            Ok((mut species, validators)) => {
+                species.name.get_or_insert_with(|| pokemon_name.into());
+                return Ok((api_url, species, validators));
+            }
+            Err(PokeApiClientError::RequestError(e))
+                if e.status() == Some(StatusCode::NOT_FOUND) =>
+            {
+                e
+            }
+            Err(e) => return Err(e),
+        };
 
-        let resp = reqwest::get(&api_url)
-            .await?
-            .error_for_status()?
-            .json::<PokemonSpecies>()
+        let pokemon_url = format!("{}api/v2/pokemon/{}", self.endpoint, pokemon_name);
+        let species_name = match self.get_json::<Pokemon>(&pokemon_url).await {
+            Ok(pokemon) => pokemon.species.name,
+            Err(_) => return Err(species_error.into()),
+        };
+        let api_url = format!("{}api/v2/pokemon-species/{}", self.endpoint, species_name);
+        let (mut species, validators) = self
+            .get_json_with_validators::<PokemonSpecies>(&api_url)
             .await?;
+        species.name.get_or_insert(species_name);
+        Ok((api_url, species, validators))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, api_url: &str) -> Result<T, PokeApiClientError> {
+        self.get_json_with_validators(api_url)
+            .await
+            .map(|(json, _)| json)
+    }
+
+    async fn get_json_with_validators<T: DeserializeOwned>(
+        &self,
+        api_url: &str,
+    ) -> Result<(T, Validators), PokeApiClientError> {
+        match self
+            .get_json_if_modified(api_url, &Validators::default())
+            .await?
+        {
+            Fetched::Modified(json, validators) => Ok((json, validators)),
+            // Nothing to compare against: the upstream answered to a request it was not sent
+            Fetched::NotModified => {
+                Err(PokeApiClientError::UnexpectedResponse(UnexpectedResponse {
+                    status: StatusCode::NOT_MODIFIED,
+                    api_url: api_url.into(),
+                }))
+            }
+        }
+    }
 
-        let language_filter = "en";
-        let description = Self::pick_random_description(&resp.descriptions, language_filter)
-            .ok_or_else(|| {
-                PokeApiClientError::TraslatableDescriptionNotFound(DescriptionNotFound {
-                    api_url,
-                    language_filter: language_filter.into(),
-                })
-            })?
-            .text
-            .as_str();
-        Ok(Self::cleanup_description(description))
+    /// Calls PokeApi API, through the cassettes if any, logging the call.
+    ///
+    /// The request is conditional if any validator is given. `Fetched::NotModified` is got whenever PokeApi API
+    /// answers with a `304 Not Modified`, leaving to the caller whether it was expected.
+    async fn get_json_if_modified<T: DeserializeOwned>(
+        &self,
+        api_url: &str,
+        validators: &Validators,
+    ) -> Result<Fetched<T>, ReqwestError> {
+        let mut call = UpstreamCall::start(&self.log, "GET", api_url);
+        let result = async {
+            let mut req = Client::new().get(api_url).headers(trace_context_headers());
+            if let Some(etag) = &validators.etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+            let resp = match &self.cassettes {
+                Some(cassettes) => cassettes.send(req, &self.log).await?,
+                None => req.send().await?,
+            };
+            call.record_response(&resp);
+            if resp.status() == StatusCode::NOT_MODIFIED {
+                return Ok(Fetched::NotModified);
+            }
+            let resp = resp.error_for_status()?;
+            let validators = Validators::of(resp.headers());
+            Ok(Fetched::Modified(resp.json::<T>().await?, validators))
+        }
+        .await;
+        log_upstream_call!(call, &result);
+        result
     }
 
     fn pick_random_description<'a>(
@@ -69,9 +309,196 @@ impl PokeApiClient {
     }
 }
 
+/// Whether a PokeApi API error means that PokeApi API is unavailable, rather than that the request is wrong.
+fn is_unavailable(error: &ReqwestError) -> bool {
+    match error.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => !error.is_decode(),
+    }
+}
+
+/// Default TTL of the `SpeciesCache` entries: species data almost never change.
+pub const DEFAULT_SPECIES_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Default max number of `SpeciesCache` entries: more than the species known by PokeApi API.
+pub const DEFAULT_SPECIES_CACHE_MAX_ENTRIES: usize = 2000;
+
+/// In-memory cache of the species got from PokeApi API, keyed by species name, with the names of the Pokémon forms and
+/// varieties resolved to their species (e.g. `charizard-mega-x` to `charizard`) as aliases.
+///
+/// Expired entries are kept to be revalidated with conditional requests and to be served as stale while PokeApi API is
+/// unavailable, up to a max number of entries (and of aliases): beyond it, the least recently used ones are evicted.
+pub struct SpeciesCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<SpeciesCacheEntries>,
+}
+
+#[derive(Default)]
+struct SpeciesCacheEntries {
+    by_species: HashMap<String, CachedSpecies>,
+    /// Species names by Pokémon form or variety name, with the time they were last used.
+    aliases: HashMap<String, (String, Instant)>,
+}
+
+#[derive(Clone)]
+struct CachedSpecies {
+    api_url: String,
+    species: PokemonSpecies,
+    validators: Validators,
+    fetched_at: Instant,
+    used_at: Instant,
+}
+
+impl CachedSpecies {
+    fn is_expired(&self, ttl: Duration) -> bool {
+        self.fetched_at.elapsed() >= ttl
+    }
+}
+
+impl SpeciesCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_SPECIES_CACHE_MAX_ENTRIES,
+            entries: Mutex::new(SpeciesCacheEntries::default()),
+        }
+    }
+
+    /// Sets the max number of cached species (and of aliases), `DEFAULT_SPECIES_CACHE_MAX_ENTRIES` by default.
+    ///
+    /// Panics if 0.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        if max_entries == 0 {
+            panic!("The max number of species cache entries must be greater than 0");
+        }
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Builds the cache with the TTL read from `POKE_API_SPECIES_CACHE_TTL_SECS` (`DEFAULT_SPECIES_CACHE_TTL` by
+    /// default), returning `None` if it's 0, and the max number of entries read from
+    /// `POKE_API_SPECIES_CACHE_MAX_ENTRIES` (`DEFAULT_SPECIES_CACHE_MAX_ENTRIES` by default).
+    ///
+    /// Panics in case of invalid env vars.
+    pub fn from_env() -> Option<Self> {
+        let ttl = parse_env_var("POKE_API_SPECIES_CACHE_TTL_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_SPECIES_CACHE_TTL);
+        let max_entries = parse_env_var("POKE_API_SPECIES_CACHE_MAX_ENTRIES")
+            .unwrap_or(DEFAULT_SPECIES_CACHE_MAX_ENTRIES);
+        Some(Self::new(ttl).with_max_entries(max_entries)).filter(|_| ttl > Duration::from_secs(0))
+    }
+
+    /// Number of cached species.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().by_species.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the species of the given Pokémon, either a species or one of its forms or varieties.
+    fn get(&self, pokemon_name: &str) -> Option<CachedSpecies> {
+        let mut entries = self.entries.lock().unwrap();
+        let entries = &mut *entries;
+        let species_name = match entries.aliases.get_mut(pokemon_name) {
+            Some((species_name, used_at)) => {
+                *used_at = Instant::now();
+                species_name.as_str()
+            }
+            None => pokemon_name,
+        };
+        let cached = entries.by_species.get_mut(species_name)?;
+        cached.used_at = Instant::now();
+        Some(cached.clone())
+    }
+
+    /// Caches the species as just fetched, also when just revalidated, keyed by its name and aliased by the given
+    /// Pokémon name if different.
+    fn insert(
+        &self,
+        pokemon_name: &str,
+        api_url: &str,
+        species: &PokemonSpecies,
+        validators: Validators,
+    ) {
+        let species_name = species.name.as_deref().unwrap_or(pokemon_name);
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if species_name != pokemon_name {
+            if !entries.aliases.contains_key(pokemon_name) {
+                evict_least_recently_used(
+                    &mut entries.aliases,
+                    self.max_entries,
+                    |(_, used_at)| *used_at,
+                );
+            }
+            entries
+                .aliases
+                .insert(pokemon_name.into(), (species_name.into(), now));
+        }
+        if !entries.by_species.contains_key(species_name) {
+            evict_least_recently_used(&mut entries.by_species, self.max_entries, |cached| {
+                cached.used_at
+            });
+        }
+        entries.by_species.insert(
+            species_name.into(),
+            CachedSpecies {
+                api_url: api_url.into(),
+                species: species.clone(),
+                validators,
+                fetched_at: now,
+                used_at: now,
+            },
+        );
+    }
+
+    /// Removes the given species, together with the alias of the given Pokémon name if any.
+    fn remove(&self, pokemon_name: &str, species: &PokemonSpecies) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.aliases.remove(pokemon_name);
+        entries
+            .by_species
+            .remove(species.name.as_deref().unwrap_or(pokemon_name));
+    }
+}
+
+/// Validators of a PokeApi API response, to request it again only if modified.
+#[derive(Clone, Debug, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn of(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+}
+
+/// Outcome of a possibly conditional PokeApi API request.
+enum Fetched<T> {
+    Modified(T, Validators),
+    NotModified,
+}
+
 #[derive(Debug)]
 pub enum PokeApiClientError {
     TraslatableDescriptionNotFound(DescriptionNotFound),
+    EvolutionChainNotFound(EvolutionChainNotFound),
+    UnexpectedResponse(UnexpectedResponse),
     RequestError(ReqwestError),
 }
 
@@ -93,10 +520,47 @@ impl Display for DescriptionNotFound {
     }
 }
 
+#[derive(Debug)]
+pub struct EvolutionChainNotFound {
+    api_url: String,
+}
+
+impl StdError for EvolutionChainNotFound {}
+
+impl Display for EvolutionChainNotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "No evolution chain found when calling PokeApi URL {:?}",
+            self.api_url
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct UnexpectedResponse {
+    status: StatusCode,
+    api_url: String,
+}
+
+impl StdError for UnexpectedResponse {}
+
+impl Display for UnexpectedResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "Unexpected response status {} when calling PokeApi URL {:?}",
+            self.status, self.api_url
+        )
+    }
+}
+
 impl StdError for PokeApiClientError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Self::TraslatableDescriptionNotFound(e) => Some(e),
+            Self::EvolutionChainNotFound(e) => Some(e),
+            Self::UnexpectedResponse(e) => Some(e),
             Self::RequestError(e) => Some(e),
         }
     }
@@ -106,6 +570,8 @@ impl Display for PokeApiClientError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             Self::TraslatableDescriptionNotFound(e) => Display::fmt(e, f),
+            Self::EvolutionChainNotFound(e) => Display::fmt(e, f),
+            Self::UnexpectedResponse(e) => Display::fmt(e, f),
             Self::RequestError(e) => Display::fmt(e, f),
         }
     }
@@ -117,20 +583,211 @@ impl From<ReqwestError> for PokeApiClientError {
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// Data of a Pokémon species, besides its descriptions, as returned by PokeApi API.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpeciesMetadata {
+    /// Name of the species, differing from the requested one for Pokémon forms and varieties (e.g. `charizard` for
+    /// `charizard-mega-x`).
+    pub name: Option<String>,
+    /// English genus (e.g. `Seed Pokémon`).
+    pub genus: Option<String>,
+    /// Generation introducing the species (e.g. `generation-i`).
+    pub generation: Option<String>,
+    /// Pokédex color (e.g. `green`).
+    pub color: Option<String>,
+    /// Habitat (e.g. `grassland`), unknown for the species of the most recent generations.
+    pub habitat: Option<String>,
+    pub is_legendary: Option<bool>,
+    pub is_mythical: Option<bool>,
+    /// Number in the national Pokédex.
+    pub pokedex_number: Option<u32>,
+    /// Whether the species was served from an expired cache entry, as PokeApi API was unavailable.
+    pub stale: bool,
+}
+
+impl From<&PokemonSpecies> for SpeciesMetadata {
+    fn from(species: &PokemonSpecies) -> Self {
+        Self {
+            name: species.name.clone(),
+            genus: species
+                .genera
+                .iter()
+                .find(|g| g.language.name == "en")
+                .map(|g| g.genus.clone()),
+            generation: species.generation.as_ref().map(|r| r.name.clone()),
+            color: species.color.as_ref().map(|r| r.name.clone()),
+            habitat: species.habitat.as_ref().map(|r| r.name.clone()),
+            is_legendary: species.is_legendary,
+            is_mythical: species.is_mythical,
+            pokedex_number: species
+                .pokedex_numbers
+                .iter()
+                .find(|n| n.pokedex.name == "national")
+                .map(|n| n.entry_number)
+                .or(species.id),
+            stale: species.stale,
+        }
+    }
+}
+
+/// Stage of an evolution chain, with the stages the species can evolve into.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvolutionStage {
+    /// Name of the species (e.g. `ivysaur`).
+    pub species: String,
+    /// Alternative ways the previous stage evolves into this one, empty for the first stage.
+    pub conditions: Vec<EvolutionConditions>,
+    pub evolves_to: Vec<EvolutionStage>,
+    /// Whether the chain was found through a species served from an expired cache entry, as PokeApi API was
+    /// unavailable. Only set on the first stage.
+    pub stale: bool,
+}
+
+/// Conditions of an evolution, all unknown ones being irrelevant to it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EvolutionConditions {
+    /// What triggers the evolution (e.g. `level-up`, `use-item` or `trade`).
+    pub trigger: Option<String>,
+    pub min_level: Option<u32>,
+    /// Item to use (e.g. `thunder-stone`).
+    pub item: Option<String>,
+    /// Item to hold while leveling up or being traded (e.g. `metal-coat`).
+    pub held_item: Option<String>,
+    pub min_happiness: Option<u32>,
+    /// Time of day of the evolution, either `day` or `night`.
+    pub time_of_day: Option<String>,
+    /// Move to know while leveling up (e.g. `ancient-power`).
+    pub known_move: Option<String>,
+    /// Location where to level up (e.g. `eterna-forest`).
+    pub location: Option<String>,
+}
+
+impl From<&ChainLink> for EvolutionStage {
+    fn from(link: &ChainLink) -> Self {
+        Self {
+            species: link.species.name.clone(),
+            conditions: link
+                .evolution_details
+                .iter()
+                .map(EvolutionConditions::from)
+                .collect(),
+            evolves_to: link.evolves_to.iter().map(Self::from).collect(),
+            stale: false,
+        }
+    }
+}
+
+impl From<&EvolutionDetail> for EvolutionConditions {
+    fn from(detail: &EvolutionDetail) -> Self {
+        let name = |resource: &Option<NamedResource>| resource.as_ref().map(|r| r.name.clone());
+        Self {
+            trigger: name(&detail.trigger),
+            min_level: detail.min_level,
+            item: name(&detail.item),
+            held_item: name(&detail.held_item),
+            min_happiness: detail.min_happiness,
+            // PokeApi API returns an empty string for evolutions at any time of day
+            time_of_day: Some(detail.time_of_day.clone()).filter(|t| !t.is_empty()),
+            known_move: name(&detail.known_move),
+            location: name(&detail.location),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
 struct PokemonSpecies {
+    #[serde(default)]
+    name: Option<String>,
     #[serde(rename = "flavor_text_entries")]
     descriptions: Vec<PokemonDescription>,
+    #[serde(default)]
+    id: Option<u32>,
+    #[serde(default)]
+    genera: Vec<Genus>,
+    #[serde(default)]
+    generation: Option<NamedResource>,
+    #[serde(default)]
+    color: Option<NamedResource>,
+    #[serde(default)]
+    habitat: Option<NamedResource>,
+    #[serde(default)]
+    is_legendary: Option<bool>,
+    #[serde(default)]
+    is_mythical: Option<bool>,
+    #[serde(default)]
+    pokedex_numbers: Vec<PokedexNumber>,
+    #[serde(default)]
+    evolution_chain: Option<ApiResource>,
+    /// Set when served from an expired `SpeciesCache` entry.
+    #[serde(skip)]
+    stale: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Genus {
+    genus: String,
+    language: Language,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct PokedexNumber {
+    entry_number: u32,
+    pokedex: NamedResource,
+}
+
+/// PokeApi API reference to another resource, of which only the name is needed.
+#[derive(Clone, Debug, Deserialize)]
+struct NamedResource {
+    name: String,
+}
+
+/// Pokémon, possibly a form or variety of its species, as returned by PokeApi API.
+#[derive(Debug, Deserialize)]
+struct Pokemon {
+    species: NamedResource,
+}
+
+/// PokeApi API reference to another resource, of which only the URL is needed.
+#[derive(Clone, Debug, Deserialize)]
+struct ApiResource {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EvolutionChain {
+    chain: ChainLink,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainLink {
+    species: NamedResource,
+    #[serde(default)]
+    evolution_details: Vec<EvolutionDetail>,
+    #[serde(default)]
+    evolves_to: Vec<ChainLink>,
 }
 
 #[derive(Debug, Deserialize)]
+struct EvolutionDetail {
+    trigger: Option<NamedResource>,
+    min_level: Option<u32>,
+    item: Option<NamedResource>,
+    held_item: Option<NamedResource>,
+    min_happiness: Option<u32>,
+    #[serde(default)]
+    time_of_day: String,
+    known_move: Option<NamedResource>,
+    location: Option<NamedResource>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
 struct PokemonDescription {
     #[serde(rename = "flavor_text")]
     text: String,
     language: Language,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct Language {
     name: String,
 }
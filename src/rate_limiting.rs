@@ -0,0 +1,230 @@
+use crate::env_helpers::parse_env_var;
+use crate::fun_translations_client::FunTranslationsTier;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Buckets of the clients that didn't call for a while are dropped once in this interval, to bound memory usage.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Token bucket holding up to `capacity` tokens, refilled continuously at a rate of `requests_per_minute`.
+///
+/// Every allowed request takes a token, or more if it costs more (e.g. a batch), so that bursts up to `capacity`
+/// requests are allowed.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Builds a full bucket.
+    pub fn new(capacity: u32, requests_per_minute: u32) -> Self {
+        Self {
+            capacity: f64::from(capacity),
+            refill_per_sec: f64::from(requests_per_minute) / 60.0,
+            tokens: f64::from(capacity),
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Takes `cost` tokens if available, returning whether the request is allowed.
+    pub fn try_acquire(&mut self, cost: u32) -> bool {
+        self.refill();
+        if self.tokens < f64::from(cost) {
+            return false;
+        }
+        self.tokens -= f64::from(cost);
+        true
+    }
+
+    /// Takes `cost` tokens if available, returning the decision to report to the client.
+    pub fn check(&mut self, cost: u32) -> RateLimitDecision {
+        let allowed = self.try_acquire(cost);
+        RateLimitDecision {
+            allowed,
+            limit: self.capacity as u32,
+            remaining: self.remaining(),
+            reset_secs: self.secs_until_full(),
+            retry_after_secs: self.secs_until(f64::from(cost)),
+        }
+    }
+
+    /// Number of requests allowed right now.
+    pub fn remaining(&self) -> u32 {
+        self.tokens.floor() as u32
+    }
+
+    /// Seconds until the next request is allowed, 0 if it's allowed right now.
+    pub fn secs_until_available(&self) -> u64 {
+        self.secs_until(1.0)
+    }
+
+    /// Seconds until the bucket is full again.
+    pub fn secs_until_full(&self) -> u64 {
+        self.secs_until(self.capacity)
+    }
+
+    fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.capacity
+    }
+
+    fn secs_until(&self, tokens: f64) -> u64 {
+        ((tokens - self.tokens).max(0.0) / self.refill_per_sec).ceil() as u64
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.refilled_at = now;
+    }
+}
+
+/// Configuration of the inbound rate limiting of the API services.
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    /// Sustained rate allowed to every client.
+    pub requests_per_minute: u32,
+    /// Max number of requests allowed at once to a client that didn't call for a while.
+    pub burst: u32,
+    /// Proxies trusted to report the client IP in the `X-Forwarded-For` header.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl RateLimitConfig {
+    /// Default rate with the free tier, conservative since FunTranslations API allows it only 5 requests per hour.
+    pub const DEFAULT_FREE_REQUESTS_PER_MINUTE: u32 = 10;
+    /// Default rate with a paid tier, whose higher upstream limits can be shared by more requests.
+    pub const DEFAULT_PAID_REQUESTS_PER_MINUTE: u32 = 120;
+
+    /// Reads the configuration of the given FunTranslations API tier from the env, `None` if its rate is 0 (i.e. rate
+    /// limiting is disabled).
+    ///
+    /// The env vars are:
+    /// - `POKESPEARE_RATE_LIMIT_PER_MINUTE`: rate with the free tier (`DEFAULT_FREE_REQUESTS_PER_MINUTE` by default)
+    /// - `POKESPEARE_PAID_RATE_LIMIT_PER_MINUTE`: rate with a paid tier (`DEFAULT_PAID_REQUESTS_PER_MINUTE` by default)
+    /// - `POKESPEARE_RATE_LIMIT_BURST`: max burst of requests (the rate of the tier by default)
+    /// - `POKESPEARE_TRUSTED_PROXIES`: comma separated IPs of the trusted proxies (none by default)
+    ///
+    /// Panics in case of invalid env vars or zero burst.
+    pub fn from_env(tier: FunTranslationsTier) -> Option<Self> {
+        let requests_per_minute = match tier {
+            FunTranslationsTier::Free => parse_env_var("POKESPEARE_RATE_LIMIT_PER_MINUTE")
+                .unwrap_or(Self::DEFAULT_FREE_REQUESTS_PER_MINUTE),
+            FunTranslationsTier::Paid => parse_env_var("POKESPEARE_PAID_RATE_LIMIT_PER_MINUTE")
+                .unwrap_or(Self::DEFAULT_PAID_REQUESTS_PER_MINUTE),
+        };
+        if requests_per_minute == 0 {
+            return None;
+        }
+        let burst = parse_env_var("POKESPEARE_RATE_LIMIT_BURST").unwrap_or(requests_per_minute);
+        if burst == 0 {
+            panic!("Invalid POKESPEARE_RATE_LIMIT_BURST 0");
+        }
+        let trusted_proxies = std::env::var("POKESPEARE_TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .map(|proxy| {
+                proxy
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid POKESPEARE_TRUSTED_PROXIES {:?}", proxy))
+            })
+            .collect();
+
+        Some(Self {
+            requests_per_minute,
+            burst,
+            trusted_proxies,
+        })
+    }
+}
+
+/// Outcome of a rate limited request, reported to the client through the `RateLimit-*` headers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Max burst of requests.
+    pub limit: u32,
+    /// Number of requests still allowed right now.
+    pub remaining: u32,
+    /// Seconds until the client is allowed its max burst again.
+    pub reset_secs: u64,
+    /// Seconds until the request would be allowed, 0 if allowed.
+    pub retry_after_secs: u64,
+}
+
+/// Inbound rate limiter with a token bucket per client (e.g. IP or API key).
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    by_client: HashMap<String, TokenBucket>,
+    pruned_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets {
+                by_client: HashMap::new(),
+                pruned_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Builds the rate limiter from `RateLimitConfig::from_env`, `None` if rate limiting is disabled.
+    ///
+    /// Panics in case of invalid env vars.
+    pub fn from_env(tier: FunTranslationsTier) -> Option<Self> {
+        RateLimitConfig::from_env(tier).map(Self::new)
+    }
+
+    /// Counts a request of the given client against its bucket, taking `cost` tokens.
+    pub fn check(&self, client: &str, cost: u32) -> RateLimitDecision {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.pruned_at.elapsed() >= PRUNE_INTERVAL {
+            buckets.by_client.retain(|_, bucket| !bucket.is_full());
+            buckets.pruned_at = Instant::now();
+        }
+        buckets
+            .by_client
+            .entry(client.to_string())
+            .or_insert_with(|| TokenBucket::new(self.config.burst, self.config.requests_per_minute))
+            .check(cost)
+    }
+
+    /// Sustained rate allowed to every client.
+    pub fn requests_per_minute(&self) -> u32 {
+        self.config.requests_per_minute
+    }
+
+    /// IP of the client of a request coming from `peer`, with the given `X-Forwarded-For` header value.
+    ///
+    /// The header is honored only if `peer` is a trusted proxy, in which case the client is the rightmost forwarded
+    /// address not belonging to a trusted proxy: the ones at its left could have been forged by the client itself.
+    pub fn client_ip(&self, peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Option<IpAddr> {
+        let trusted = &self.config.trusted_proxies;
+        let mut client = peer?;
+        if !trusted.contains(&client) {
+            return Some(client);
+        }
+        for forwarded in forwarded_for.unwrap_or_default().rsplit(',') {
+            match forwarded.trim().parse() {
+                Ok(ip) if trusted.contains(&ip) => client = ip,
+                Ok(ip) => return Some(ip),
+                Err(_) => break,
+            }
+        }
+        Some(client)
+    }
+}
@@ -1,48 +1,1163 @@
-use crate::fun_translations_client::FunTranslationsClient;
-use crate::poke_api_client::PokeApiClient;
-use crate::services_api_models::ShakespeareanDescriptionApiResponse;
-use actix_web::web::{Data, Path, ServiceConfig};
-use actix_web::{get, Error, HttpResponse};
+use crate::api_keys::{ApiKeys, API_KEY_HEADER};
+use crate::cassettes::Cassettes;
+use crate::content_negotiation::{negotiate, render_html, render_xml};
+use crate::descriptions_cache::DescriptionsCache;
+use crate::env_helpers::parse_env_var;
+use crate::errors::{ApiError, ApiErrorResponseBody, ApiErrorResponseCode, RateLimitedBy};
+use crate::errors::{RequestError, ShakespeareanDescriptionError};
+use crate::evolution_narratives;
+use crate::fun_translations_client::{
+    ApiSecret, FunTranslationsClient, FunTranslationsTier, DEFAULT_MAX_CHUNK_CHARS,
+};
+use crate::http_caching::{is_not_modified, strong_etag, CacheControlConfig};
+use crate::log_helpers::{error, error_chain, get_discard_logger, info, o, warn, Logger};
+use crate::openapi::ApiDoc;
+use crate::poke_api_client::{EvolutionStage, PokeApiClient, SpeciesCache, SpeciesMetadata};
+use crate::rate_limiting::{RateLimitDecision, RateLimiter};
+use crate::services_api_models::{
+    EvolutionApiResponse, EvolutionStageApiResponse, ShakespeareanDescriptionApiResponse,
+    ShakespeareanDescriptionsBatchApiItem, ShakespeareanDescriptionsBatchApiRequest,
+    ShakespeareanDescriptionsBatchApiResponse, ShakespeareanDescriptionsBatchApiResult,
+    SpeciesApiResponse, SpeciesInclude, TranslationApiRequest, TranslationApiResponse,
+};
+use crate::shutdown::Readiness;
+use crate::telemetry::in_server_span;
+use crate::upstreams::{DescriptionSource, Translator};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use actix_web::error::JsonPayloadError;
+use actix_web::error::ResponseError;
+use actix_web::http::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, CACHE_CONTROL, ETAG, LINK, RETRY_AFTER, VARY,
+    WARNING,
+};
+use actix_web::http::StatusCode;
+use actix_web::web::{self, Data, Json, JsonConfig, Path, Query, ServiceConfig};
+use actix_web::{get, post, Error, HttpMessage, HttpRequest, HttpResponse};
+use futures::future::{ok, LocalBoxFuture};
+use futures::{StreamExt, TryFutureExt};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::net::IpAddr;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use utoipa::OpenApi;
 
-/// App services configuration utility to setup required App `Data` and API services.
+/// App state shared by the App instances of all the server workers (e.g. caches and quotas), to be built only once.
+#[derive(Clone)]
+pub struct SharedState {
+    pub cache: Data<DescriptionsCache>,
+    /// API keys allowed to call the API services, `None` if authentication is disabled.
+    pub api_keys: Option<Data<ApiKeys>>,
+    /// Inbound rate limiter of the API services, `None` if rate limiting is disabled.
+    pub rate_limiter: Option<Data<RateLimiter>>,
+    /// Failed by the graceful shutdown of the server.
+    pub readiness: Data<Readiness>,
+    /// Cache of the species got from PokeApi API, `None` if disabled.
+    pub species_cache: Option<Arc<SpeciesCache>>,
+    pub batch_config: Data<BatchConfig>,
+}
+
+impl SharedState {
+    /// Panics in case of invalid env vars.
+    pub fn from_env() -> Self {
+        Self {
+            cache: Data::new(DescriptionsCache::from_env()),
+            api_keys: ApiKeys::from_env().map(Data::new),
+            rate_limiter: RateLimiter::from_env(FunTranslationsTier::from_env()).map(Data::new),
+            readiness: Data::new(Readiness::default()),
+            species_cache: SpeciesCache::from_env().map(Arc::new),
+            batch_config: Data::new(BatchConfig::from_env()),
+        }
+    }
+}
+
+/// Clients of the upstreams called by the API services: PokeApi and FunTranslations API ones by default.
+#[derive(Clone)]
+pub struct UpstreamClients {
+    pub description_source: Arc<dyn DescriptionSource>,
+    pub translator: Arc<dyn Translator>,
+}
+
+impl UpstreamClients {
+    pub fn new(
+        description_source: impl DescriptionSource + 'static,
+        translator: impl Translator + 'static,
+    ) -> Self {
+        Self {
+            description_source: Arc::new(description_source),
+            translator: Arc::new(translator),
+        }
+    }
+
+    /// Builds the clients from the env, logging their upstream calls with the given `Logger` and caching the PokeApi
+    /// species in the given `SpeciesCache`, if any.
+    ///
+    /// Panics in case of missing or invalid (e.g not URLs) required env vars.
+    pub fn from_env(log: &Logger, species_cache: Option<Arc<SpeciesCache>>) -> Self {
+        let poke_api_endpoint =
+            std::env::var("POKE_API_ENDPOINT").expect("Missing required POKE_API_ENDPOINT");
+        let fun_translations_api_endpoint = std::env::var("FUN_TRANSLATIONS_API_ENDPOINT")
+            .expect("Missing required FUN_TRANSLATIONS_API_ENDPOINT");
+
+        let mut poke_api = PokeApiClient::new(&poke_api_endpoint)
+            .with_logger(log.new(o!("upstream" => "poke_api")));
+        let mut fun_translations = FunTranslationsClient::new(&fun_translations_api_endpoint)
+            .with_logger(log.new(o!("upstream" => "fun_translations")))
+            .with_max_chunk_chars(
+                parse_env_var("FUN_TRANSLATIONS_MAX_CHUNK_CHARS")
+                    .unwrap_or(DEFAULT_MAX_CHUNK_CHARS),
+            );
+        if let Some(species_cache) = species_cache {
+            poke_api = poke_api.with_species_cache(species_cache);
+        }
+        if let Some(api_secret) = ApiSecret::from_env() {
+            fun_translations = fun_translations.with_api_secret(api_secret);
+        }
+        if let Some(cassettes) = Cassettes::from_env() {
+            poke_api = poke_api.with_cassettes(cassettes.clone());
+            fun_translations = fun_translations.with_cassettes(cassettes);
+        }
+        Self::new(poke_api, fun_translations)
+    }
+}
+
+/// App services configuration utility to setup required App `Data` and API services, with the `UpstreamClients` built
+/// from the env.
+///
+/// The given `Logger` is registered as App `Data` and used by the HTTP clients to log their upstream calls.
+/// The given `SharedState` is registered as App `Data` too, so that it's shared by the App instances of all the
+/// server workers.
 ///
 /// Panics in case of missing or invalid (e.g not URLs) required env vars.
-pub fn config_app(cfg: &mut ServiceConfig) {
-    let poke_api_endpoint =
-        std::env::var("POKE_API_ENDPOINT").expect("Missing required POKE_API_ENDPOINT");
-    let fun_translations_api_endpoint = std::env::var("FUN_TRANSLATIONS_API_ENDPOINT")
-        .expect("Missing required FUN_TRANSLATIONS_API_ENDPOINT");
+pub fn config_app(cfg: &mut ServiceConfig, log: &Logger, shared_state: &SharedState) {
+    config_app_with(
+        cfg,
+        log,
+        shared_state,
+        UpstreamClients::from_env(log, shared_state.species_cache.clone()),
+    )
+}
+
+/// Like `config_app`, but with already built `UpstreamClients`, so that no required env var is read and App instances
+/// can call different upstreams (e.g. the isolated mock servers of parallel tests).
+///
+/// The given `Logger` is not set on the clients, which keep their own.
+pub fn config_app_with(
+    cfg: &mut ServiceConfig,
+    log: &Logger,
+    shared_state: &SharedState,
+    clients: UpstreamClients,
+) {
+    cfg.data(log.clone());
+    cfg.app_data(Data::from(clients.description_source));
+    cfg.app_data(Data::from(clients.translator));
+    cfg.app_data(shared_state.cache.clone());
+    cfg.app_data(shared_state.readiness.clone());
+    cfg.app_data(shared_state.batch_config.clone());
+    cfg.data(TranslationConfig::from_env());
+    cfg.data(CacheControlConfig::from_env());
+    cfg.app_data(json_config(
+        parse_env_var("POKESPEARE_MAX_BODY_BYTES").unwrap_or(16 * 1024),
+    ));
+    cfg.service(get_openapi_spec);
+    cfg.service(get_docs);
+    cfg.service(get_readiness);
+    let (api_keys, auth_log) = (shared_state.api_keys.clone(), log.clone());
+    let authenticate_v1 =
+        move |req, srv: &mut _| authenticate(api_keys.clone(), auth_log.clone(), req, srv);
+    let limits = Limits {
+        rate_limiter: shared_state.rate_limiter.clone(),
+        api_keys: shared_state.api_keys.clone(),
+    };
+    let rate_limit_log = log.clone();
+    let rate_limit_v1 =
+        move |req, srv: &mut _| rate_limit(limits.clone(), rate_limit_log.clone(), req, srv);
+    // The last wrapped middleware is the first called: requests are authenticated before being rate limited
+    cfg.service(
+        web::scope("/v1")
+            .wrap_fn(rate_limit_v1.clone())
+            .wrap_fn(authenticate_v1.clone())
+            .configure(config_v1),
+    );
+    // Must be the last registered service: the unprefixed scope catches every request
+    cfg.service(
+        web::scope("")
+            .wrap_fn(rate_limit_v1)
+            .wrap_fn(authenticate_v1)
+            .wrap_fn(|req, srv| {
+                let successor = format!("</v1{}>; rel=\"successor-version\"", req.path());
+                srv.call(req).map_ok(move |mut resp| {
+                    let headers = resp.headers_mut();
+                    headers.insert(
+                        HeaderName::from_static("deprecation"),
+                        HeaderValue::from_static("true"),
+                    );
+                    headers.insert(
+                        HeaderName::from_static("sunset"),
+                        HeaderValue::from_static(UNVERSIONED_API_SUNSET),
+                    );
+                    if let Ok(link) = HeaderValue::from_str(&successor) {
+                        headers.insert(LINK, link);
+                    }
+                    resp
+                })
+            })
+            .configure(config_v1),
+    );
+}
+
+/// Authenticates the requests of the wrapped API services with their API key, if authentication is enabled, leaving
+/// their limits to `rate_limit`.
+///
+/// Authenticated calls are logged together with the name of the API key owner, so that usages can be told apart.
+fn authenticate<S>(
+    api_keys: Option<Data<ApiKeys>>,
+    log: Logger,
+    req: ServiceRequest,
+    srv: &mut S,
+) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse, Error = Error>,
+    S::Future: 'static,
+{
+    let api_keys = match api_keys {
+        Some(api_keys) => api_keys,
+        None => return Box::pin(srv.call(req)),
+    };
+    let key = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok());
+    match api_keys.authenticate(key) {
+        Ok(name) => {
+            let log = log.new(o!("api_key" => name.to_string()));
+            req.extensions_mut().insert(ApiKeyOwner(name.to_string()));
+            let (method, path) = (req.method().to_string(), req.path().to_string());
+            let resp = srv.call(req);
+            Box::pin(async move {
+                let resp = resp.await?;
+                info!(log, "API call"; "method" => method, "path" => path, "status" => resp.status().as_u16());
+                Ok(resp)
+            })
+        }
+        Err(e) => {
+            let e = log_error_response(&log, e);
+            Box::pin(ok(req.error_response(e)))
+        }
+    }
+}
+
+/// Name of the owner of the API key of an authenticated request, stored in the request extensions.
+struct ApiKeyOwner(String);
+
+/// Limits applied to the requests of the API services.
+#[derive(Clone)]
+struct Limits {
+    rate_limiter: Option<Data<RateLimiter>>,
+    api_keys: Option<Data<ApiKeys>>,
+}
+
+/// Rate limits the requests of the wrapped API services, if rate limiting or authentication is enabled, adding the
+/// `RateLimit-*` headers to their responses.
+///
+/// Authenticated requests are limited by `ApiKeys::limit`, i.e. by the rate and daily quota of their API key, while
+/// the others per client IP. Requests take a token and the API services can charge them more, through the
+/// `RateLimitedClient` in their extensions, for the upstream calls beyond the first one (e.g. of a batch).
+/// Rejected requests get a `429 Too Many Requests`, with a `Retry-After` header if rejected by a rate.
+fn rate_limit<S>(
+    limits: Limits,
+    log: Logger,
+    req: ServiceRequest,
+    srv: &mut S,
+) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse, Error = Error>,
+    S::Future: 'static,
+{
+    let api_key_owner = req
+        .extensions()
+        .get::<ApiKeyOwner>()
+        .map(|ApiKeyOwner(name)| name.clone());
+    let client = match (api_key_owner, &limits.api_keys, &limits.rate_limiter) {
+        (Some(name), Some(_), _) => RateLimitedClient::new(limits, RateLimitKey::ApiKey(name)),
+        (_, _, Some(rate_limiter)) => {
+            let forwarded_for = req
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok());
+            let ip = rate_limiter.client_ip(req.peer_addr().map(|addr| addr.ip()), forwarded_for);
+            RateLimitedClient::new(limits, RateLimitKey::Ip(ip))
+        }
+        _ => return Box::pin(srv.call(req)),
+    };
+    let client = Rc::new(client);
+
+    if let Err(e) = client.charge(1) {
+        let e = log_error_response(&log.new(o!("client" => client.to_string())), e);
+        let mut resp = req.error_response(e);
+        if let Some(decision) = client.decision() {
+            insert_rate_limit_headers(resp.headers_mut(), &decision);
+            if !decision.allowed {
+                resp.headers_mut()
+                    .insert(RETRY_AFTER, decision.retry_after_secs.into());
+            }
+        }
+        return Box::pin(ok(resp));
+    }
+    req.extensions_mut().insert(client.clone());
+    let resp = srv.call(req);
+    Box::pin(async move {
+        let mut resp = resp.await?;
+        if let Some(decision) = client.decision() {
+            insert_rate_limit_headers(resp.headers_mut(), &decision);
+        }
+        Ok(resp)
+    })
+}
+
+/// Client of a rate limited request, stored in its extensions so that API services can charge it for the upstream
+/// calls beyond the first one, with `charge`.
+struct RateLimitedClient {
+    limits: Limits,
+    key: RateLimitKey,
+    /// Decision of the last charge, reported through the `RateLimit-*` headers.
+    decision: RefCell<Option<RateLimitDecision>>,
+}
+
+enum RateLimitKey {
+    /// Name of the API key owner.
+    ApiKey(String),
+    /// IP of the client, if known.
+    Ip(Option<IpAddr>),
+}
+
+impl RateLimitedClient {
+    fn new(limits: Limits, key: RateLimitKey) -> Self {
+        Self {
+            limits,
+            key,
+            decision: RefCell::new(None),
+        }
+    }
 
-    let poke_api_client = PokeApiClient::new(&poke_api_endpoint);
-    let fun_translations_client = FunTranslationsClient::new(&fun_translations_api_endpoint);
+    /// Charges the request `cost` more tokens, returning the error to return if they aren't available (in which case
+    /// none is taken).
+    fn charge(&self, cost: u32) -> Result<(), RequestError> {
+        let rate_limiter = self.limits.rate_limiter.as_ref().map(|r| r.get_ref());
+        let (decision, result) = match (&self.key, &self.limits.api_keys, rate_limiter) {
+            (RateLimitKey::ApiKey(name), Some(api_keys), _) => {
+                api_keys.limit(name, rate_limiter, cost)
+            }
+            (_, _, Some(rate_limiter)) => {
+                let decision = rate_limiter.check(&self.to_string(), cost);
+                let result = if decision.allowed {
+                    Ok(())
+                } else {
+                    Err(RequestError::TooManyRequests(format!(
+                        "Client exceeded the rate of {} requests per minute, retry in {} seconds",
+                        rate_limiter.requests_per_minute(),
+                        decision.retry_after_secs
+                    )))
+                };
+                (Some(decision), result)
+            }
+            _ => (None, Ok(())),
+        };
+        if decision.is_some() {
+            *self.decision.borrow_mut() = decision;
+        }
+        result
+    }
 
-    cfg.data(poke_api_client);
-    cfg.data(fun_translations_client);
+    fn decision(&self) -> Option<RateLimitDecision> {
+        self.decision.borrow().clone()
+    }
+}
+
+impl Display for RateLimitedClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.key {
+            RateLimitKey::ApiKey(name) => write!(f, "api_key:{}", name),
+            RateLimitKey::Ip(Some(ip)) => write!(f, "ip:{}", ip),
+            RateLimitKey::Ip(None) => f.write_str("unknown"),
+        }
+    }
+}
+
+fn insert_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static("ratelimit-limit"),
+        decision.limit.into(),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-remaining"),
+        decision.remaining.into(),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-reset"),
+        decision.reset_secs.into(),
+    );
+}
+
+/// Version 1 API services, also exposed without version prefix as deprecated aliases.
+///
+/// A new API version goes into its own `config_vN`, mounted under `/vN` by `config_app`. New services must be
+/// documented in the OpenAPI spec and listed in the `V1_OPERATIONS` of its tests, which check that they all match.
+fn config_v1(cfg: &mut ServiceConfig) {
+    cfg.service(get_shakespearean_descriptions_batch);
     cfg.service(get_shakespearean_description);
+    cfg.service(get_shakespearean_evolution);
+    cfg.service(translate_text);
+}
+
+/// Configuration of the JSON request bodies extraction, turning its errors into JSON API error responses.
+///
+/// Bodies larger than `max_body_bytes` are rejected with a `413 Payload Too Large`, invalid ones with a
+/// `400 Bad Request`.
+fn json_config(max_body_bytes: usize) -> JsonConfig {
+    JsonConfig::default()
+        .limit(max_body_bytes)
+        .error_handler(move |e, req| {
+            let log = req
+                .app_data::<Data<Logger>>()
+                .map(|log| Logger::clone(log))
+                .unwrap_or_else(get_discard_logger);
+            match e {
+                JsonPayloadError::Overflow => log_error_response(
+                    &log,
+                    RequestError::PayloadTooLarge(format!(
+                        "Request body exceeds {} bytes",
+                        max_body_bytes
+                    )),
+                )
+                .into(),
+                e => log_error_response(&log, RequestError::Invalid(e.to_string())).into(),
+            }
+        })
+}
+
+/// Limits of the `translate_text` API service.
+pub struct TranslationConfig {
+    /// Max number of characters of the text to translate.
+    pub max_text_chars: usize,
+}
+
+impl TranslationConfig {
+    /// Reads the limits from `POKESPEARE_TRANSLATION_MAX_TEXT_CHARS` (1000 by default).
+    ///
+    /// Panics in case of invalid env var.
+    pub fn from_env() -> Self {
+        Self {
+            max_text_chars: parse_env_var("POKESPEARE_TRANSLATION_MAX_TEXT_CHARS").unwrap_or(1000),
+        }
+    }
+}
+
+/// Limits of the `get_shakespearean_descriptions_batch` API service.
+pub struct BatchConfig {
+    /// Max number of Pokémon per request.
+    pub max_size: usize,
+    /// Max number of Pokémon whose descriptions are concurrently requested to the upstreams.
+    pub concurrency: usize,
+}
+
+impl BatchConfig {
+    /// Reads the limits from `POKESPEARE_BATCH_MAX_SIZE` (20 by default) and `POKESPEARE_BATCH_CONCURRENCY` (4 by
+    /// default).
+    ///
+    /// Panics in case of invalid env vars.
+    pub fn from_env() -> Self {
+        let concurrency = parse_env_var("POKESPEARE_BATCH_CONCURRENCY").unwrap_or(4);
+        if concurrency == 0 {
+            panic!("Invalid POKESPEARE_BATCH_CONCURRENCY 0");
+        }
+        Self {
+            max_size: parse_env_var("POKESPEARE_BATCH_MAX_SIZE").unwrap_or(20),
+            concurrency,
+        }
+    }
+}
+
+/// `Sunset` header value of the deprecated unversioned API services.
+pub const UNVERSIONED_API_SUNSET: &str = "Sat, 01 May 2027 00:00:00 GMT";
+
+/// Media types of the representations of a Pokémon "Shakespearean" description, in order of preference.
+pub const DESCRIPTION_MEDIA_TYPES: &[&str] = &[
+    "application/json",
+    "text/plain",
+    "text/html",
+    "application/xml",
+];
+
+/// Media types of the representations of a Pokémon "Shakespearean" description able to carry its species data, in
+/// order of preference.
+pub const SPECIES_MEDIA_TYPES: &[&str] = &["application/json", "application/xml"];
+
+/// Query parameters of the `get_shakespearean_description` API service.
+#[derive(Deserialize)]
+pub(crate) struct DescriptionQuery {
+    /// Comma separated `SpeciesInclude`s.
+    include: Option<String>,
 }
 
 /// API service that, given a Pokémon name, returns its "Shakespearean" description.
 ///
+/// Pokémon forms and varieties (e.g. `charizard-mega-x`) get the description of their species, named in the response.
+/// Descriptions got from species data cached before PokeApi API became unavailable are marked as `stale`, in every
+/// representation and with a `Warning: 110` header, and they are not cached.
+/// Species data are included only if requested through the `include` query parameter, calling the description source
+/// even for cached descriptions and FunTranslations API for the Shakespearean genus.
+/// The description is represented according to the `Accept` header among `DESCRIPTION_MEDIA_TYPES` (JSON by default),
+/// or among `SPECIES_MEDIA_TYPES` if species data are included.
+/// Successful responses carry a strong `ETag` and the configured `Cache-Control`: requests whose `If-None-Match` matches
+/// the `ETag` of a cached description get a `304 Not Modified` without calling the upstreams.
 /// In case of errors, returns a JSON reponse with a descriptive code (`code`) and an indicative error detail
 /// (`message`).
+#[utoipa::path(
+    get,
+    context_path = "/v1",
+    path = "/pokemon/{pokemon_name}",
+    params(
+        ("pokemon_name" = String, Path, description = "Name of the Pokémon (e.g. `bulbasaur`)"),
+        ("include" = Option<String>, Query, description = "Comma separated species data to include: `genus`, `shakespearean_genus` (translated by FunTranslations API), `generation`, `color`, `habitat`, `legendary`, `mythical` and `pokedex_number`"),
+        ("If-None-Match" = Option<String>, Header, description = "`ETag`s of the descriptions already known by the client"),
+    ),
+    responses(
+        (status = 200, description = "Shakespearean description of the Pokémon: as JSON, as plain text (the description only), as embeddable HTML page or as XML (JSON and XML only with `include`)", content(
+            ("application/json" = ShakespeareanDescriptionApiResponse),
+            ("text/plain" = String),
+            ("text/html" = String),
+            ("application/xml" = String),
+        ), headers(
+            ("ETag" = String, description = "Strong entity tag derived from the response body"),
+            ("Cache-Control" = String, description = "Configured caching directives"),
+            ("Warning" = String, description = "`110 - \"Response is Stale\"`, only for stale descriptions"),
+        )),
+        (status = 304, description = "Not modified: the `If-None-Match` header matches the `ETag` of the description", headers(
+            ("ETag" = String, description = "Strong entity tag derived from the response body"),
+        )),
+        (status = 400, description = "Invalid Pokémon name or unknown include (`INVALID_REQUEST`)", body = ApiErrorResponseBody),
+        (status = 406, description = "None of the available representations, JSON and XML only with `include`, is acceptable (`NOT_ACCEPTABLE`)", body = ApiErrorResponseBody),
+        (status = 404, description = "Unknown Pokémon (`POKE_API_ERROR`) or Pokémon without English descriptions (`TRANSLATABLE_DESCRIPTION_NOT_FOUND`)", body = ApiErrorResponseBody),
+        (status = 429, description = "FunTranslations API limits (`limited_by: UPSTREAM`) or client limits (`limited_by: LOCAL`) exceeded (`TOO_MANY_REQUESTS`, `QUOTA_EXCEEDED`)", body = ApiErrorResponseBody),
+        (status = 500, description = "Unexpected PokeApi (`POKE_API_ERROR`) or FunTranslations (`FUN_TRANSLATIONS_ERROR`) API error, invalid FunTranslations API secret (`FUN_TRANSLATIONS_UNAUTHORIZED`) or unexpected error of custom description sources (`DESCRIPTION_SOURCE_ERROR`) or translators (`TRANSLATOR_ERROR`)", body = ApiErrorResponseBody),
+    )
+)]
 #[get("/pokemon/{pokemon_name}")]
-async fn get_shakespearean_description(
-    poke_api_client: Data<PokeApiClient>,
-    fun_translations_client: Data<FunTranslationsClient>,
+pub(crate) async fn get_shakespearean_description(
+    req: HttpRequest,
+    log: Data<Logger>,
+    description_source: Data<dyn DescriptionSource>,
+    translator: Data<dyn Translator>,
+    cache: Data<DescriptionsCache>,
+    cache_control: Data<CacheControlConfig>,
     pokemon_name: Path<String>,
 ) -> Result<HttpResponse, Error> {
-    let pokemon_description = poke_api_client
-        .get_random_description(&pokemon_name)
+    in_server_span(&req, async {
+        validate_pokemon_name(&pokemon_name).map_err(|e| log_error_response(&log, e))?;
+        let query = Query::<DescriptionQuery>::from_query(req.query_string())
+            .map_err(|e| log_error_response(&log, RequestError::Invalid(e.to_string())))?;
+        let includes = SpeciesInclude::parse_all(query.include.as_deref().unwrap_or_default())
+            .map_err(|name| {
+                let names = SpeciesInclude::ALL.iter().map(|(name, _)| *name);
+                log_error_response(
+                    &log,
+                    RequestError::Invalid(format!(
+                        "Unknown include {:?}, expected any of: {}",
+                        name,
+                        names.collect::<Vec<_>>().join(", ")
+                    )),
+                )
+            })?;
+
+        let media_types = if includes.is_empty() {
+            DESCRIPTION_MEDIA_TYPES
+        } else {
+            SPECIES_MEDIA_TYPES
+        };
+        let accept = req.headers().get(ACCEPT).and_then(|v| v.to_str().ok());
+        let media_type = negotiate(accept, media_types).ok_or_else(|| {
+            log_error_response(
+                &log,
+                RequestError::NotAcceptable(format!(
+                    "Available media types: {}",
+                    media_types.join(", ")
+                )),
+            )
+        })?;
+
+        let (shakespearean_description, species) = if includes.is_empty() {
+            let description = match get_cached_shakespearean_description(&pokemon_name, &cache) {
+                Some(description) => description,
+                None => get_and_cache_shakespearean_description(
+                    &pokemon_name,
+                    description_source.as_ref(),
+                    translator.as_ref(),
+                    &cache,
+                )
+                .await
+                .map_err(|e| log_error_response(&log, e))?,
+            };
+            (description, None)
+        } else {
+            let (description, species) = get_shakespearean_description_with_species(
+                &pokemon_name,
+                &includes,
+                description_source.as_ref(),
+                translator.as_ref(),
+                &cache,
+            )
+            .await
+            .map_err(|e| log_error_response(&log, e))?;
+            (description, Some(species))
+        };
+
+        Ok(description_response(
+            &req,
+            &cache_control.description,
+            &ShakespeareanDescriptionApiResponse {
+                name: pokemon_name.to_string(),
+                description: shakespearean_description.description,
+                species_name: shakespearean_description.species_name,
+                stale: shakespearean_description.stale,
+                species,
+            },
+            media_type,
+        ))
+    })
+    .await
+}
+
+/// Builds the response representing the description as the given media type, one of `DESCRIPTION_MEDIA_TYPES`.
+///
+/// The response carries a strong `ETag` derived from its body and it's a bodyless `304 Not Modified` if the request
+/// `If-None-Match` header matches it. Stale descriptions carry a `Warning: 110` header too, since the plain text
+/// representation can't tell them.
+fn description_response(
+    req: &HttpRequest,
+    cache_control: &HeaderValue,
+    description: &ShakespeareanDescriptionApiResponse,
+    media_type: &str,
+) -> HttpResponse {
+    let (content_type, body) = match media_type {
+        "text/plain" => ("text/plain; charset=utf-8", description.description.clone()),
+        "text/html" => ("text/html; charset=utf-8", render_html(description)),
+        "application/xml" => ("application/xml; charset=utf-8", render_xml(description)),
+        _ => (
+            "application/json",
+            serde_json::to_string(description)
+                .expect("Can't serialize ShakespeareanDescriptionApiResponse"),
+        ),
+    };
+    let etag = strong_etag(body.as_bytes());
+    let not_modified = is_not_modified(req.headers(), &etag);
+
+    let mut resp = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    resp.header(VARY, "Accept")
+        .header(ETAG, etag)
+        .header(CACHE_CONTROL, cache_control.clone());
+    if description.stale {
+        resp.header(WARNING, "110 - \"Response is Stale\"");
+    }
+    if not_modified {
+        resp.finish()
+    } else {
+        resp.content_type(content_type).body(body)
+    }
+}
+
+/// API service that, given a Pokémon name, returns the evolution chain of its species together with its "Shakespearean"
+/// narrative.
+///
+/// The narrative is told from the chain structure (e.g. "Bulbasaur evolves into Ivysaur at level 16...") and its
+/// translation is cached like the descriptions, while the chain is always requested to the description source.
+/// Errors are returned like the ones of `get_shakespearean_description`.
+#[utoipa::path(
+    get,
+    context_path = "/v1",
+    path = "/pokemon/{pokemon_name}/evolution",
+    params(
+        ("pokemon_name" = String, Path, description = "Name of the Pokémon (e.g. `ivysaur`)"),
+    ),
+    responses(
+        (status = 200, description = "Evolution chain of the Pokémon species with its Shakespearean narrative", body = EvolutionApiResponse),
+        (status = 400, description = "Invalid Pokémon name (`INVALID_REQUEST`)", body = ApiErrorResponseBody),
+        (status = 404, description = "Unknown Pokémon (`POKE_API_ERROR`) or species without evolution chain (`EVOLUTION_CHAIN_NOT_FOUND`)", body = ApiErrorResponseBody),
+        (status = 429, description = "FunTranslations API limits (`limited_by: UPSTREAM`) or client limits (`limited_by: LOCAL`) exceeded (`TOO_MANY_REQUESTS`, `QUOTA_EXCEEDED`)", body = ApiErrorResponseBody),
+        (status = 500, description = "Unexpected PokeApi (`POKE_API_ERROR`) or FunTranslations (`FUN_TRANSLATIONS_ERROR`) API error, invalid FunTranslations API secret (`FUN_TRANSLATIONS_UNAUTHORIZED`) or description sources (`DESCRIPTION_SOURCE_ERROR`) and translators (`TRANSLATOR_ERROR`) other than the default ones failing or not supporting evolution chains", body = ApiErrorResponseBody),
+    )
+)]
+#[get("/pokemon/{pokemon_name}/evolution")]
+pub(crate) async fn get_shakespearean_evolution(
+    req: HttpRequest,
+    log: Data<Logger>,
+    description_source: Data<dyn DescriptionSource>,
+    translator: Data<dyn Translator>,
+    cache: Data<DescriptionsCache>,
+    pokemon_name: Path<String>,
+) -> Result<HttpResponse, Error> {
+    in_server_span(&req, async {
+        validate_pokemon_name(&pokemon_name).map_err(|e| log_error_response(&log, e))?;
+        let (chain, narrative) = get_shakespearean_evolution_chain(
+            &pokemon_name,
+            description_source.as_ref(),
+            translator.as_ref(),
+            &cache,
+        )
+        .await
+        .map_err(|e| log_error_response(&log, e))?;
+
+        Ok(HttpResponse::Ok().json(EvolutionApiResponse {
+            name: pokemon_name.to_string(),
+            chain: EvolutionStageApiResponse::from(&chain),
+            narrative,
+        }))
+    })
+    .await
+}
+
+/// API service that, given a list of Pokémon names, returns their "Shakespearean" descriptions.
+///
+/// Each Pokémon gets its own result, either its description or the error preventing to get it, so that a failure
+/// doesn't fail the whole batch. Cached descriptions are served first, without calling the upstreams, while the
+/// missing ones are requested with bounded concurrency. Once FunTranslations API limits are exceeded, the Pokémon not
+/// yet translated are not requested anymore and are marked as `quota_limited`.
+#[utoipa::path(
+    post,
+    context_path = "/v1",
+    path = "/pokemon/batch",
+    request_body = ShakespeareanDescriptionsBatchApiRequest,
+    responses(
+        (status = 200, description = "Results of the requested Pokémon, in request order", body = ShakespeareanDescriptionsBatchApiResponse),
+        (status = 400, description = "No Pokémon or more Pokémon than allowed (`INVALID_REQUEST`)", body = ApiErrorResponseBody),
+        (status = 429, description = "Client limits exceeded (`TOO_MANY_REQUESTS`, `QUOTA_EXCEEDED`, `limited_by: LOCAL`)", body = ApiErrorResponseBody),
+    )
+)]
+#[post("/pokemon/batch")]
+pub(crate) async fn get_shakespearean_descriptions_batch(
+    req: HttpRequest,
+    log: Data<Logger>,
+    description_source: Data<dyn DescriptionSource>,
+    translator: Data<dyn Translator>,
+    cache: Data<DescriptionsCache>,
+    batch_config: Data<BatchConfig>,
+    batch: Json<ShakespeareanDescriptionsBatchApiRequest>,
+) -> Result<HttpResponse, Error> {
+    in_server_span(&req, async {
+        let names = &batch.names;
+        if names.is_empty() || names.len() > batch_config.max_size {
+            return Err(log_error_response(
+                &log,
+                RequestError::Invalid(format!(
+                    "Expected from 1 to {} Pokémon names, got {}",
+                    batch_config.max_size,
+                    names.len()
+                )),
+            )
+            .into());
+        }
+
+        let mut items: HashMap<&str, ShakespeareanDescriptionsBatchApiItem> = HashMap::new();
+        for name in names {
+            if let Err(e) = validate_pokemon_name(name) {
+                items.insert(name, error_batch_item(name, log_error_response(&log, e)));
+            } else if let Some(description) = get_cached_shakespearean_description(name, &cache) {
+                let mut item = description_batch_item(name, description);
+                item.cached = true;
+                items.insert(name, item);
+            }
+        }
+
+        let mut requested = HashSet::new();
+        let misses = names
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !items.contains_key(name) && requested.insert(*name))
+            .collect::<Vec<_>>();
+        let quota_exceeded = AtomicBool::new(false);
+        let rate_limited_client = req.extensions().get::<Rc<RateLimitedClient>>().cloned();
+        let fetched = futures::stream::iter(misses.into_iter().enumerate())
+            .map(|(i, name)| {
+                let quota_exceeded = &quota_exceeded;
+                let (log, description_source) = (&log, description_source.as_ref());
+                let (translator, cache) = (translator.as_ref(), &cache);
+                let rate_limited_client = rate_limited_client.clone();
+                async move {
+                    if quota_exceeded.load(Ordering::SeqCst) {
+                        return (name, quota_limited_batch_item(name));
+                    }
+                    // The first Pokémon not cached is paid by the token taken by the request itself
+                    let charge = match rate_limited_client {
+                        Some(client) if i > 0 => client.charge(1),
+                        _ => Ok(()),
+                    };
+                    if let Err(e) = charge {
+                        return (name, error_batch_item(name, log_error_response(log, e)));
+                    }
+                    let result = get_and_cache_shakespearean_description(
+                        name,
+                        description_source,
+                        translator,
+                        cache,
+                    )
+                    .await;
+                    let item = match result {
+                        Ok(description) => description_batch_item(name, description),
+                        Err(e) => {
+                            let e = log_error_response(log, e);
+                            if e.is_too_many_requests() {
+                                quota_exceeded.store(true, Ordering::SeqCst);
+                            }
+                            error_batch_item(name, e)
+                        }
+                    };
+                    (name, item)
+                }
+            })
+            .buffered(batch_config.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        items.extend(fetched);
+
+        Ok(
+            HttpResponse::Ok().json(ShakespeareanDescriptionsBatchApiResponse {
+                results: names
+                    .iter()
+                    .map(|name| items[name.as_str()].clone())
+                    .collect(),
+            }),
+        )
+    })
+    .await
+}
+
+/// API service that translates the given text in the given FunTranslations style, without going through PokeApi.
+///
+/// Errors are returned like the ones of `get_shakespearean_description`.
+#[utoipa::path(
+    post,
+    context_path = "/v1",
+    path = "/translate",
+    request_body = TranslationApiRequest,
+    responses(
+        (status = 200, description = "Translated text", body = TranslationApiResponse),
+        (status = 400, description = "Invalid request body, empty or too long text or invalid style (`INVALID_REQUEST`)", body = ApiErrorResponseBody),
+        (status = 404, description = "Unknown FunTranslations style (`FUN_TRANSLATIONS_ERROR`)", body = ApiErrorResponseBody),
+        (status = 413, description = "Request body too large (`PAYLOAD_TOO_LARGE`)", body = ApiErrorResponseBody),
+        (status = 429, description = "FunTranslations API limits (`limited_by: UPSTREAM`) or client limits (`limited_by: LOCAL`) exceeded (`TOO_MANY_REQUESTS`, `QUOTA_EXCEEDED`)", body = ApiErrorResponseBody),
+        (status = 500, description = "Unexpected FunTranslations API error (`FUN_TRANSLATIONS_ERROR`), invalid FunTranslations API secret (`FUN_TRANSLATIONS_UNAUTHORIZED`) or unexpected error of custom translators (`TRANSLATOR_ERROR`)", body = ApiErrorResponseBody),
+    )
+)]
+#[post("/translate")]
+pub(crate) async fn translate_text(
+    req: HttpRequest,
+    log: Data<Logger>,
+    translator: Data<dyn Translator>,
+    translation_config: Data<TranslationConfig>,
+    translation: Json<TranslationApiRequest>,
+) -> Result<HttpResponse, Error> {
+    in_server_span(&req, async {
+        let TranslationApiRequest { text, style } = translation.into_inner();
+        let text_chars = text.chars().count();
+        let invalid_request = if text.trim().is_empty() {
+            Some("Empty text".to_string())
+        } else if text_chars > translation_config.max_text_chars {
+            Some(format!(
+                "Text exceeds {} characters, got {}",
+                translation_config.max_text_chars, text_chars
+            ))
+        } else if style.is_empty()
+            || !style
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            Some(format!("Invalid style {:?}", style))
+        } else {
+            None
+        };
+        if let Some(message) = invalid_request {
+            return Err(log_error_response(&log, RequestError::Invalid(message)).into());
+        }
+
+        let translation = translator
+            .translate_to(&text, &style)
+            .await
+            .map_err(|e| log_error_response(&log, e))?;
+
+        Ok(HttpResponse::Ok().json(TranslationApiResponse { style, translation }))
+    })
+    .await
+}
+
+/// "Shakespearean" description of a Pokémon, with the name of the species it comes from if the Pokémon is one of its
+/// forms or varieties.
+struct ShakespeareanDescription {
+    description: String,
+    species_name: Option<String>,
+    /// Whether the species data were stale, as PokeApi API was unavailable.
+    stale: bool,
+}
+
+impl ShakespeareanDescription {
+    fn new(pokemon_name: &str, description: String, metadata: &SpeciesMetadata) -> Self {
+        Self {
+            description,
+            species_name: metadata
+                .name
+                .clone()
+                .filter(|species_name| species_name != pokemon_name),
+            stale: metadata.stale,
+        }
+    }
+}
+
+/// Checks that the given Pokémon name is made of lowercase ASCII letters, digits and dashes only, like PokeApi ones,
+/// before it's used in cache keys: the ones of descriptions are the Pokémon names themselves, while the other entries
+/// are suffixed by `#`, so they can't collide.
+fn validate_pokemon_name(pokemon_name: &str) -> Result<(), RequestError> {
+    let valid = !pokemon_name.is_empty()
+        && pokemon_name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if valid {
+        Ok(())
+    } else {
+        Err(RequestError::Invalid(format!(
+            "Invalid Pokémon name {:?}, expected lowercase letters, digits and dashes only",
+            pokemon_name
+        )))
+    }
+}
+
+/// Cache key of the species of a Pokémon form or variety.
+fn species_cache_key(pokemon_name: &str) -> String {
+    format!("{}#species", pokemon_name)
+}
+
+/// Gets the cached "Shakespearean" description of the given Pokémon, if any.
+fn get_cached_shakespearean_description(
+    pokemon_name: &str,
+    cache: &DescriptionsCache,
+) -> Option<ShakespeareanDescription> {
+    Some(ShakespeareanDescription {
+        description: cache.get(pokemon_name)?,
+        species_name: cache.get(&species_cache_key(pokemon_name)),
+        stale: false,
+    })
+}
+
+/// Caches the "Shakespearean" description of the given Pokémon, unless it's stale: it would be served as fresh once
+/// PokeApi API is available again.
+fn cache_shakespearean_description(
+    pokemon_name: &str,
+    description: &ShakespeareanDescription,
+    cache: &DescriptionsCache,
+) {
+    if description.stale {
+        return;
+    }
+    cache.insert(pokemon_name, &description.description);
+    if let Some(species_name) = &description.species_name {
+        cache.insert(&species_cache_key(pokemon_name), species_name);
+    }
+}
+
+/// Gets the "Shakespearean" description of the given Pokémon from the upstreams and caches it.
+async fn get_and_cache_shakespearean_description(
+    pokemon_name: &str,
+    description_source: &dyn DescriptionSource,
+    translator: &dyn Translator,
+    cache: &DescriptionsCache,
+) -> Result<ShakespeareanDescription, ShakespeareanDescriptionError> {
+    let (pokemon_description, metadata) = description_source
+        .get_description_with_metadata(pokemon_name)
         .await?;
+    let shakespearean_description = ShakespeareanDescription::new(
+        pokemon_name,
+        translator.translate(&pokemon_description).await?,
+        &metadata,
+    );
+    cache_shakespearean_description(pokemon_name, &shakespearean_description, cache);
+    Ok(shakespearean_description)
+}
 
-    let shakespearean_description = fun_translations_client
-        .translate(&pokemon_description)
+/// Gets the "Shakespearean" description of the given Pokémon together with the requested species data.
+///
+/// Species data come from the description source even when the description is cached, while the genus is translated
+/// only if requested, to be cached too.
+async fn get_shakespearean_description_with_species(
+    pokemon_name: &str,
+    includes: &[SpeciesInclude],
+    description_source: &dyn DescriptionSource,
+    translator: &dyn Translator,
+    cache: &DescriptionsCache,
+) -> Result<(ShakespeareanDescription, SpeciesApiResponse), ShakespeareanDescriptionError> {
+    let (pokemon_description, metadata) = description_source
+        .get_description_with_metadata(pokemon_name)
         .await?;
+    let shakespearean_description = match cache.get(pokemon_name) {
+        Some(description) => ShakespeareanDescription::new(pokemon_name, description, &metadata),
+        None => {
+            let description = ShakespeareanDescription::new(
+                pokemon_name,
+                translator.translate(&pokemon_description).await?,
+                &metadata,
+            );
+            cache_shakespearean_description(pokemon_name, &description, cache);
+            description
+        }
+    };
 
-    Ok(
-        HttpResponse::Ok().json(ShakespeareanDescriptionApiResponse {
-            name: pokemon_name.to_string(),
-            description: shakespearean_description,
+    let mut species = SpeciesApiResponse::default();
+    for include in includes {
+        match include {
+            SpeciesInclude::Genus => species.genus = metadata.genus.clone(),
+            SpeciesInclude::ShakespeareanGenus => {
+                let cache_key = format!("{}#genus", pokemon_name);
+                species.shakespearean_genus = match (&metadata.genus, cache.get(&cache_key)) {
+                    (None, _) => None,
+                    (Some(_), Some(cached)) => Some(cached),
+                    (Some(genus), None) => {
+                        let shakespearean_genus = translator.translate(genus).await?;
+                        if !metadata.stale {
+                            cache.insert(&cache_key, &shakespearean_genus);
+                        }
+                        Some(shakespearean_genus)
+                    }
+                };
+            }
+            SpeciesInclude::Generation => species.generation = metadata.generation.clone(),
+            SpeciesInclude::Color => species.color = metadata.color.clone(),
+            SpeciesInclude::Habitat => species.habitat = metadata.habitat.clone(),
+            SpeciesInclude::Legendary => species.is_legendary = metadata.is_legendary,
+            SpeciesInclude::Mythical => species.is_mythical = metadata.is_mythical,
+            SpeciesInclude::PokedexNumber => species.pokedex_number = metadata.pokedex_number,
+        }
+    }
+    Ok((shakespearean_description, species))
+}
+
+/// Gets the evolution chain of the given Pokémon together with its "Shakespearean" narrative, translated only if not
+/// cached yet.
+///
+/// Narratives are cached by the first stage of the chain, so that all its Pokémon share the same translation, unless
+/// the chain was found through stale species data.
+async fn get_shakespearean_evolution_chain(
+    pokemon_name: &str,
+    description_source: &dyn DescriptionSource,
+    translator: &dyn Translator,
+    cache: &DescriptionsCache,
+) -> Result<(EvolutionStage, String), ShakespeareanDescriptionError> {
+    let chain = description_source.get_evolution_chain(pokemon_name).await?;
+    let cache_key = format!("{}#evolution", chain.species);
+    let narrative = match cache.get(&cache_key) {
+        Some(narrative) => narrative,
+        None => {
+            let narrative = translator
+                .translate(&evolution_narratives::narrate(&chain))
+                .await?;
+            if !chain.stale {
+                cache.insert(&cache_key, &narrative);
+            }
+            narrative
+        }
+    };
+    Ok((chain, narrative))
+}
+
+fn description_batch_item(
+    name: &str,
+    description: ShakespeareanDescription,
+) -> ShakespeareanDescriptionsBatchApiItem {
+    ShakespeareanDescriptionsBatchApiItem {
+        name: name.into(),
+        status: StatusCode::OK.as_u16(),
+        cached: false,
+        quota_limited: false,
+        result: ShakespeareanDescriptionsBatchApiResult::Description(
+            ShakespeareanDescriptionApiResponse {
+                name: name.into(),
+                description: description.description,
+                species_name: description.species_name,
+                stale: description.stale,
+                species: None,
+            },
+        ),
+    }
+}
+
+/// Batch item of a Pokémon whose description couldn't be got, `quota_limited` if either FunTranslations API limits or
+/// the client ones were exceeded.
+fn error_batch_item<E: ApiError>(name: &str, e: E) -> ShakespeareanDescriptionsBatchApiItem {
+    ShakespeareanDescriptionsBatchApiItem {
+        name: name.into(),
+        status: e.status_code().as_u16(),
+        cached: false,
+        quota_limited: e.status_code() == StatusCode::TOO_MANY_REQUESTS,
+        result: ShakespeareanDescriptionsBatchApiResult::Error(e.api_error_response_body()),
+    }
+}
+
+fn quota_limited_batch_item(name: &str) -> ShakespeareanDescriptionsBatchApiItem {
+    ShakespeareanDescriptionsBatchApiItem {
+        name: name.into(),
+        status: StatusCode::TOO_MANY_REQUESTS.as_u16(),
+        cached: false,
+        quota_limited: true,
+        result: ShakespeareanDescriptionsBatchApiResult::Error(ApiErrorResponseBody {
+            code: ApiErrorResponseCode::TooManyRequests,
+            message: "Not requested: FunTranslations API limits exceeded by a previous Pokémon of the batch"
+                .into(),
+            limited_by: Some(RateLimitedBy::Upstream),
         }),
-    )
+    }
+}
+
+/// API service returning the OpenAPI 3 specification of the API services.
+#[get("/openapi.json")]
+async fn get_openapi_spec(cache_control: Data<CacheControlConfig>) -> HttpResponse {
+    HttpResponse::Ok()
+        .header(CACHE_CONTROL, cache_control.openapi_spec.clone())
+        .json(ApiDoc::openapi())
+}
+
+/// API service returning an HTML page documenting the API services, rendered from their OpenAPI specification.
+#[get("/docs")]
+async fn get_docs(cache_control: Data<CacheControlConfig>) -> HttpResponse {
+    HttpResponse::Ok()
+        .header(CACHE_CONTROL, cache_control.docs.clone())
+        .content_type("text/html; charset=utf-8")
+        .body(include_str!("../static/docs.html"))
+}
+
+/// Readiness probe of the server: `200 OK` while it accepts requests, `503 Service Unavailable` once its graceful
+/// shutdown started.
+#[get("/ready")]
+async fn get_readiness(readiness: Data<Readiness>) -> HttpResponse {
+    if readiness.is_ready() {
+        HttpResponse::Ok().json(serde_json::json!({ "status": "ready" }))
+    } else {
+        HttpResponse::ServiceUnavailable().json(serde_json::json!({ "status": "shutting_down" }))
+    }
+}
+
+/// Logs an error about to be returned as API response together with its full `source()` chain.
+///
+/// Server errors are logged as `error` while client ones as `warn`.
+fn log_error_response<E: ResponseError + StdError + 'static>(log: &Logger, e: E) -> E {
+    let status = e.status_code();
+    if status.is_server_error() {
+        error!(log, "Error response"; "status" => status.as_u16(), "error_chain" => ?error_chain(&e));
+    } else {
+        warn!(log, "Error response"; "status" => status.as_u16(), "error_chain" => ?error_chain(&e));
+    }
+    e
 }
@@ -1,8 +1,261 @@
+use crate::errors::ApiErrorResponseBody;
+use crate::poke_api_client::{EvolutionConditions, EvolutionStage};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Response of the `get_shakespearean_description` API service.
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
 pub struct ShakespeareanDescriptionApiResponse {
+    /// Name of the Pokémon.
+    #[schema(example = "bulbasaur")]
     pub name: String,
+    /// One of the Pokémon English descriptions, translated by FunTranslations API.
+    #[schema(
+        example = "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon."
+    )]
     pub description: String,
+    /// Name of the species whose description was used, only if the Pokémon is one of its forms or varieties (e.g.
+    /// `charizard` for `charizard-mega-x`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "charizard")]
+    pub species_name: Option<String>,
+    /// Whether the description comes from species data cached before PokeApi API became unavailable, only if so.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// Species data of the Pokémon, only if requested through the `include` query parameter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species: Option<SpeciesApiResponse>,
+}
+
+/// Species data of a Pokémon, each included only if requested.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct SpeciesApiResponse {
+    /// English genus of the species.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Seed Pokémon")]
+    pub genus: Option<String>,
+    /// English genus of the species, translated by FunTranslations API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Grain pokémon")]
+    pub shakespearean_genus: Option<String>,
+    /// Generation introducing the species.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "generation-i")]
+    pub generation: Option<String>,
+    /// Pokédex color of the species.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "green")]
+    pub color: Option<String>,
+    /// Habitat of the species.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "grassland")]
+    pub habitat: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = false)]
+    pub is_legendary: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = false)]
+    pub is_mythical: Option<bool>,
+    /// Number of the species in the national Pokédex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 1)]
+    pub pokedex_number: Option<u32>,
+}
+
+/// Species data that can be included in the `get_shakespearean_description` API service response.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpeciesInclude {
+    Genus,
+    ShakespeareanGenus,
+    Generation,
+    Color,
+    Habitat,
+    Legendary,
+    Mythical,
+    PokedexNumber,
+}
+
+impl SpeciesInclude {
+    /// All the includes, in the order of their `include` query parameter names.
+    pub const ALL: &'static [(&'static str, Self)] = &[
+        ("genus", Self::Genus),
+        ("shakespearean_genus", Self::ShakespeareanGenus),
+        ("generation", Self::Generation),
+        ("color", Self::Color),
+        ("habitat", Self::Habitat),
+        ("legendary", Self::Legendary),
+        ("mythical", Self::Mythical),
+        ("pokedex_number", Self::PokedexNumber),
+    ];
+
+    /// Parses the comma separated `include` query parameter value, returning the name of the first unknown include in
+    /// case of errors.
+    pub fn parse_all(include: &str) -> Result<Vec<Self>, String> {
+        let mut includes = Vec::new();
+        for name in include.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let include = Self::ALL
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, include)| *include)
+                .ok_or_else(|| name.to_string())?;
+            if !includes.contains(&include) {
+                includes.push(include);
+            }
+        }
+        Ok(includes)
+    }
+}
+
+/// Response of the `get_shakespearean_evolution` API service.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct EvolutionApiResponse {
+    /// Name of the Pokémon.
+    #[schema(example = "ivysaur")]
+    pub name: String,
+    /// Evolution chain the Pokémon belongs to, from its first stage.
+    pub chain: EvolutionStageApiResponse,
+    /// Narrative of the evolution chain, translated by FunTranslations API.
+    #[schema(
+        example = "Bulbasaur evolves into ivysaur at level 16. Ivysaur evolves into venusaur at level 32."
+    )]
+    pub narrative: String,
+}
+
+/// Stage of an evolution chain, with the stages the species can evolve into.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct EvolutionStageApiResponse {
+    /// Name of the species.
+    #[schema(example = "ivysaur")]
+    pub species: String,
+    /// Alternative ways the previous stage evolves into this one, missing for the first stage.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<EvolutionConditionsApiResponse>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evolves_to: Vec<EvolutionStageApiResponse>,
+}
+
+impl From<&EvolutionStage> for EvolutionStageApiResponse {
+    fn from(stage: &EvolutionStage) -> Self {
+        Self {
+            species: stage.species.clone(),
+            conditions: stage.conditions.iter().map(Into::into).collect(),
+            evolves_to: stage.evolves_to.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Conditions of an evolution, missing when irrelevant to it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct EvolutionConditionsApiResponse {
+    /// What triggers the evolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "level-up")]
+    pub trigger: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 16)]
+    pub min_level: Option<u32>,
+    /// Item to use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "thunder-stone")]
+    pub item: Option<String>,
+    /// Item to hold while leveling up or being traded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "metal-coat")]
+    pub held_item: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 220)]
+    pub min_happiness: Option<u32>,
+    /// Either `day` or `night`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "night")]
+    pub time_of_day: Option<String>,
+    /// Move to know while leveling up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "ancient-power")]
+    pub known_move: Option<String>,
+    /// Location where to level up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "eterna-forest")]
+    pub location: Option<String>,
+}
+
+impl From<&EvolutionConditions> for EvolutionConditionsApiResponse {
+    fn from(conditions: &EvolutionConditions) -> Self {
+        Self {
+            trigger: conditions.trigger.clone(),
+            min_level: conditions.min_level,
+            item: conditions.item.clone(),
+            held_item: conditions.held_item.clone(),
+            min_happiness: conditions.min_happiness,
+            time_of_day: conditions.time_of_day.clone(),
+            known_move: conditions.known_move.clone(),
+            location: conditions.location.clone(),
+        }
+    }
+}
+
+/// Request of the `get_shakespearean_descriptions_batch` API service.
+#[derive(Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct ShakespeareanDescriptionsBatchApiRequest {
+    /// Names of the Pokémon.
+    #[schema(example = json!(["bulbasaur", "charmander"]))]
+    pub names: Vec<String>,
+}
+
+/// Response of the `get_shakespearean_descriptions_batch` API service.
+#[derive(Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct ShakespeareanDescriptionsBatchApiResponse {
+    /// Results of the requested Pokémon, in request order.
+    pub results: Vec<ShakespeareanDescriptionsBatchApiItem>,
+}
+
+/// Result of a single Pokémon of a `get_shakespearean_descriptions_batch` API service call.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct ShakespeareanDescriptionsBatchApiItem {
+    /// Name of the Pokémon.
+    #[schema(example = "bulbasaur")]
+    pub name: String,
+    /// HTTP status code the `get_shakespearean_description` API service would have returned for the Pokémon.
+    #[schema(example = 200)]
+    pub status: u16,
+    /// Whether the description was served from cache, without calling the upstreams.
+    pub cached: bool,
+    /// Whether the description is missing because of exceeded FunTranslations API limits.
+    pub quota_limited: bool,
+    pub result: ShakespeareanDescriptionsBatchApiResult,
+}
+
+/// Either the "Shakespearean" description of a Pokémon or the error preventing to get it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum ShakespeareanDescriptionsBatchApiResult {
+    Description(ShakespeareanDescriptionApiResponse),
+    Error(ApiErrorResponseBody),
+}
+
+/// Request of the `translate_text` API service.
+#[derive(Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct TranslationApiRequest {
+    /// Text to translate.
+    #[schema(example = "Sparky, the fastest Pikachu in town")]
+    pub text: String,
+    /// FunTranslations style of the translation (`shakespeare` by default).
+    #[serde(default = "default_translation_style")]
+    #[schema(example = "shakespeare")]
+    pub style: String,
+}
+
+fn default_translation_style() -> String {
+    "shakespeare".into()
+}
+
+/// Response of the `translate_text` API service.
+#[derive(Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+pub struct TranslationApiResponse {
+    /// FunTranslations style of the translation.
+    #[schema(example = "shakespeare")]
+    pub style: String,
+    /// The requested text, translated by FunTranslations API.
+    #[schema(example = "Sparky, the fastest pikachu in town")]
+    pub translation: String,
 }
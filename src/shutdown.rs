@@ -0,0 +1,60 @@
+use crate::env_helpers::parse_env_var;
+use actix_rt::signal::unix::{signal, SignalKind};
+use futures::future::{select, Either};
+use std::io::Error as IoError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Readiness of the server to accept new requests, failed as soon as its graceful shutdown starts so that load
+/// balancers stop routing requests to it.
+#[derive(Debug, Default)]
+pub struct Readiness {
+    shutting_down: AtomicBool,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        !self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    pub fn fail(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Timings of the graceful shutdown of the server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShutdownConfig {
+    /// How long the server keeps accepting requests once its readiness is failed, until load balancers notice it.
+    pub readiness_delay: Duration,
+    /// How long in-flight requests are waited for before being dropped.
+    pub drain_timeout: Duration,
+}
+
+impl ShutdownConfig {
+    /// Reads the timings from `POKESPEARE_SHUTDOWN_READINESS_DELAY_SECS` (5 seconds by default) and
+    /// `POKESPEARE_SHUTDOWN_TIMEOUT_SECS` (30 seconds by default).
+    ///
+    /// Panics in case of invalid env vars.
+    pub fn from_env() -> Self {
+        Self {
+            readiness_delay: Duration::from_secs(
+                parse_env_var("POKESPEARE_SHUTDOWN_READINESS_DELAY_SECS").unwrap_or(5),
+            ),
+            drain_timeout: Duration::from_secs(
+                parse_env_var("POKESPEARE_SHUTDOWN_TIMEOUT_SECS").unwrap_or(30),
+            ),
+        }
+    }
+}
+
+/// Waits for a `SIGTERM` or a `SIGINT`, returning the name of the received one.
+pub async fn shutdown_signal() -> Result<&'static str, IoError> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
+    let received = match select(Box::pin(sigterm.recv()), Box::pin(sigint.recv())).await {
+        Either::Left(_) => "SIGTERM",
+        Either::Right(_) => "SIGINT",
+    };
+    Ok(received)
+}
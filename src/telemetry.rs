@@ -0,0 +1,184 @@
+#[cfg(feature = "server")]
+use actix_web::{http::HeaderMap as ActixHeaderMap, Error, HttpRequest, HttpResponse};
+use opentelemetry::global::{self, TracerProviderGuard};
+#[cfg(feature = "server")]
+use opentelemetry::propagation::Extractor;
+use opentelemetry::propagation::Injector;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::trace::{self as sdktrace, BatchSpanProcessor};
+use opentelemetry::sdk::Resource;
+use opentelemetry::trace::{FutureExt, SpanKind, StatusCode, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_otlp::{Exporter, ExporterConfig};
+use reqwest::header::{HeaderMap as ReqwestHeaderMap, HeaderName, HeaderValue};
+use std::error::Error as StdError;
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// Name of the tracer used for every pokespeare span.
+pub const TRACER_NAME: &str = "pokespeare";
+
+/// Sets up W3C trace context propagation and, when `OTEL_EXPORTER_OTLP_ENDPOINT` is set, the export of spans over
+/// OTLP to that endpoint.
+///
+/// Spans are exported in batches by a dedicated runtime, so that exports never compete with (or block) the API
+/// services one. The returned guard flushes the pending spans and stops the export once dropped.
+///
+/// Panics if the OTLP exporter can't be set up.
+pub fn init_tracing_from_env() -> Option<TracingGuard> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+    let runtime = tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .core_threads(1)
+        .thread_name("otlp-exporter")
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| panic!("Can't build OTLP exporter runtime, error: {:?}", e));
+    let handle = runtime.handle().clone();
+
+    let provider = runtime.enter(|| {
+        let exporter = Exporter::new(ExporterConfig {
+            endpoint: endpoint.clone(),
+            ..ExporterConfig::default()
+        })
+        .unwrap_or_else(|e| panic!("Can't build OTLP exporter to {}, error: {:?}", endpoint, e));
+        let batch = BatchSpanProcessor::builder(
+            exporter,
+            move |fut| handle.spawn(fut),
+            tokio::time::delay_for,
+            tokio::time::interval,
+        )
+        .build();
+        sdktrace::TracerProvider::builder()
+            .with_batch_exporter(batch)
+            .with_config(sdktrace::config().with_resource(Resource::new(vec![
+                KeyValue::new("service.name", env!("CARGO_PKG_NAME")),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ])))
+            .build()
+    });
+
+    Some(TracingGuard {
+        _provider: global::set_tracer_provider(provider),
+        _runtime: runtime,
+    })
+}
+
+/// Keeps the OTLP export running until dropped.
+// Fields are dropped in declaration order: the provider flushes its spans before its runtime is stopped.
+pub struct TracingGuard {
+    _provider: TracerProviderGuard,
+    _runtime: Runtime,
+}
+
+/// Runs the given API service future within a server span named after the request method and matched route pattern
+/// (e.g. `GET /v1/pokemon/{pokemon_name}`).
+///
+/// The span is a child of the W3C trace context found in the request headers, if any, and it's a root span otherwise.
+#[cfg(feature = "server")]
+pub async fn in_server_span<F>(req: &HttpRequest, fut: F) -> Result<HttpResponse, Error>
+where
+    F: Future<Output = Result<HttpResponse, Error>>,
+{
+    let parent_cx =
+        global::get_text_map_propagator(|p| p.extract(&ActixHeaderExtractor(req.headers())));
+    let name = format!(
+        "{} {}",
+        req.method(),
+        req.match_pattern().unwrap_or_else(|| req.path().into())
+    );
+    let tracer = global::tracer(TRACER_NAME);
+    let span = tracer
+        .span_builder(&name)
+        .with_kind(SpanKind::Server)
+        .with_parent_context(parent_cx)
+        .with_attributes(vec![
+            KeyValue::new("http.method", req.method().to_string()),
+            KeyValue::new("http.target", req.uri().to_string()),
+        ])
+        .start(&tracer);
+    let cx = Context::current_with_span(span);
+
+    let result = fut.with_context(cx.clone()).await;
+
+    let status_code = match &result {
+        Ok(resp) => resp.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    cx.span().set_attribute(KeyValue::new(
+        "http.status_code",
+        i64::from(status_code.as_u16()),
+    ));
+    if status_code.is_server_error() {
+        cx.span()
+            .set_status(StatusCode::Error, status_code.to_string());
+    }
+    result
+}
+
+/// Runs the given upstream call future within a client span named `name`, child of the current context.
+pub async fn in_client_span<F, T, E>(name: &str, fut: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: StdError,
+{
+    let tracer = global::tracer(TRACER_NAME);
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Client)
+        .start(&tracer);
+    let cx = Context::current_with_span(span);
+
+    let result = fut.with_context(cx.clone()).await;
+
+    if let Err(e) = &result {
+        cx.span().set_status(StatusCode::Error, e.to_string());
+    }
+    result
+}
+
+/// Returns the headers carrying the W3C trace context of the current span, to be sent to upstreams.
+pub fn trace_context_headers() -> ReqwestHeaderMap {
+    let mut headers = ReqwestHeaderMap::new();
+    global::get_text_map_propagator(|p| {
+        p.inject_context(
+            &Context::current(),
+            &mut ReqwestHeaderInjector(&mut headers),
+        )
+    });
+    headers
+}
+
+/// Records the given attribute on the current span.
+pub fn set_current_span_attribute(attribute: KeyValue) {
+    Context::current().span().set_attribute(attribute);
+}
+
+#[cfg(feature = "server")]
+struct ActixHeaderExtractor<'a>(&'a ActixHeaderMap);
+
+#[cfg(feature = "server")]
+impl<'a> Extractor for ActixHeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+struct ReqwestHeaderInjector<'a>(&'a mut ReqwestHeaderMap);
+
+impl<'a> Injector for ReqwestHeaderInjector<'a> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::env_helpers::parse_env_var;
+use crate::log_helpers::{error, info, Logger};
+use rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls::sign::{any_supported_type, CertifiedKey};
+use rustls::{ClientHello, NoClientAuth, ResolvesServerCert, ServerConfig};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// TLS configuration of the server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsConfig {
+    /// Address of the TLS listener, served together with the plain one if any.
+    pub listen_addr: String,
+    /// PEM file of the certificate chain, leaf certificate first.
+    pub cert_file: PathBuf,
+    /// PEM file of the PKCS#8 or RSA private key of the certificate.
+    pub key_file: PathBuf,
+    /// How often certificate and key files are checked for changes.
+    pub reload_interval: Duration,
+}
+
+impl TlsConfig {
+    /// Reads the configuration from the env, returning `None` if `POKESPEARE_TLS_LISTEN_ADDR` is not set, meaning
+    /// that TLS is disabled.
+    ///
+    /// The other env vars are:
+    /// - `POKESPEARE_TLS_CERT_FILE`: required PEM file of the certificate chain
+    /// - `POKESPEARE_TLS_KEY_FILE`: required PEM file of the private key
+    /// - `POKESPEARE_TLS_RELOAD_INTERVAL_SECS`: how often files are checked for changes (10 seconds by default)
+    ///
+    /// Panics in case of invalid or missing required env vars.
+    pub fn from_env() -> Option<Self> {
+        let listen_addr = std::env::var("POKESPEARE_TLS_LISTEN_ADDR").ok()?;
+        Some(Self {
+            listen_addr,
+            cert_file: std::env::var("POKESPEARE_TLS_CERT_FILE")
+                .expect("Missing required POKESPEARE_TLS_CERT_FILE")
+                .into(),
+            key_file: std::env::var("POKESPEARE_TLS_KEY_FILE")
+                .expect("Missing required POKESPEARE_TLS_KEY_FILE")
+                .into(),
+            reload_interval: Duration::from_secs(
+                parse_env_var("POKESPEARE_TLS_RELOAD_INTERVAL_SECS").unwrap_or(10),
+            ),
+        })
+    }
+
+    /// Builds the rustls server configuration, serving the certificate loaded by a `ReloadingCertResolver`.
+    ///
+    /// Returns an error if the certificate or the key can't be loaded.
+    pub fn server_config(&self, log: &Logger) -> Result<ServerConfig, String> {
+        let mut server_config = ServerConfig::new(NoClientAuth::new());
+        server_config.cert_resolver = Arc::new(ReloadingCertResolver::new(self, log.clone())?);
+        Ok(server_config)
+    }
+}
+
+/// Resolves the certificate of every TLS handshake, reloading it once certificate or key files change.
+///
+/// Files are checked at most once per `TlsConfig::reload_interval`, during handshakes. If they can't be loaded (e.g.
+/// because they're being replaced) the previous certificate is kept and the load is retried at the next check.
+pub struct ReloadingCertResolver {
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    reload_interval: Duration,
+    log: Logger,
+    certified_key: RwLock<CertifiedKey>,
+    reload_state: Mutex<ReloadState>,
+}
+
+struct ReloadState {
+    checked_at: Instant,
+    /// Modification times of the certificate and key files of the served certificate.
+    modified: (Option<SystemTime>, Option<SystemTime>),
+}
+
+impl ReloadingCertResolver {
+    /// Returns an error if the certificate or the key can't be loaded.
+    pub fn new(config: &TlsConfig, log: Logger) -> Result<Self, String> {
+        let modified = (modified(&config.cert_file), modified(&config.key_file));
+        let certified_key = load_certified_key(&config.cert_file, &config.key_file)?;
+        Ok(Self {
+            cert_file: config.cert_file.clone(),
+            key_file: config.key_file.clone(),
+            reload_interval: config.reload_interval,
+            log,
+            certified_key: RwLock::new(certified_key),
+            reload_state: Mutex::new(ReloadState {
+                checked_at: Instant::now(),
+                modified,
+            }),
+        })
+    }
+
+    fn reload_if_changed(&self) {
+        let mut state = self.reload_state.lock().unwrap();
+        if state.checked_at.elapsed() < self.reload_interval {
+            return;
+        }
+        state.checked_at = Instant::now();
+        let modified = (modified(&self.cert_file), modified(&self.key_file));
+        if modified == state.modified {
+            return;
+        }
+        match load_certified_key(&self.cert_file, &self.key_file) {
+            Ok(certified_key) => {
+                *self.certified_key.write().unwrap() = certified_key;
+                state.modified = modified;
+                info!(self.log, "Reloaded TLS certificate"; "cert_file" => ?self.cert_file);
+            }
+            Err(e) => {
+                error!(self.log, "Can't reload TLS certificate, keeping the previous one"; "error" => e)
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<CertifiedKey> {
+        self.reload_if_changed();
+        Some(self.certified_key.read().unwrap().clone())
+    }
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    std::fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+fn load_certified_key(cert_file: &Path, key_file: &Path) -> Result<CertifiedKey, String> {
+    let open = |file: &Path| {
+        File::open(file)
+            .map(BufReader::new)
+            .map_err(|e| format!("Can't open {:?}, error: {}", file, e))
+    };
+    let chain = certs(&mut open(cert_file)?)
+        .ok()
+        .filter(|chain| !chain.is_empty())
+        .ok_or_else(|| format!("No PEM certificates in {:?}", cert_file))?;
+    let key = pkcs8_private_keys(&mut open(key_file)?)
+        .ok()
+        .filter(|keys| !keys.is_empty())
+        .or_else(|| rsa_private_keys(&mut open(key_file).ok()?).ok())
+        .and_then(|keys| keys.into_iter().next())
+        .ok_or_else(|| format!("No PEM PKCS#8 or RSA private key in {:?}", key_file))?;
+    let signing_key = any_supported_type(&key)
+        .map_err(|_| format!("Unsupported private key in {:?}", key_file))?;
+    Ok(CertifiedKey::new(chain, Arc::new(signing_key)))
+}
@@ -0,0 +1,223 @@
+use crate::env_helpers::parse_env_var;
+use actix_web::dev::{Service, ServiceRequest};
+use actix_web::web::{self, Data, Form, Path, Query, ServiceConfig};
+use actix_web::{get, post, HttpResponse};
+use futures::future::{ok, Either};
+use rand::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Configuration of the local stand-in of PokeApi and FunTranslations APIs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StubConfig {
+    /// Directory with the `pokemon-species/{name}.json`, `pokemon/{name}.json` and `evolution-chain/{id}.json`
+    /// responses and the `translate/{style}.json` dictionaries.
+    pub fixtures_dir: PathBuf,
+    /// Delay added to every response.
+    pub latency: Duration,
+    /// Probability of a `500 Internal Server Error` response, from 0 to 1.
+    pub error_rate: f64,
+    /// Probability of a `429 Too Many Requests` response, from 0 to 1.
+    pub too_many_requests_rate: f64,
+}
+
+impl StubConfig {
+    /// Reads the configuration from the following env vars:
+    /// - `UPSTREAM_STUB_FIXTURES_DIR`: fixtures directory (`fixtures` by default)
+    /// - `UPSTREAM_STUB_LATENCY_MS`: delay added to every response (0 by default)
+    /// - `UPSTREAM_STUB_ERROR_RATE`: probability of 500 responses (0 by default)
+    /// - `UPSTREAM_STUB_TOO_MANY_REQUESTS_RATE`: probability of 429 responses (0 by default)
+    ///
+    /// Panics in case of invalid env vars, rates included.
+    pub fn from_env() -> Self {
+        Self {
+            fixtures_dir: std::env::var("UPSTREAM_STUB_FIXTURES_DIR")
+                .unwrap_or_else(|_| "fixtures".into())
+                .into(),
+            latency: Duration::from_millis(parse_env_var("UPSTREAM_STUB_LATENCY_MS").unwrap_or(0)),
+            error_rate: parse_rate_env_var("UPSTREAM_STUB_ERROR_RATE"),
+            too_many_requests_rate: parse_rate_env_var("UPSTREAM_STUB_TOO_MANY_REQUESTS_RATE"),
+        }
+    }
+}
+
+/// Parses the probability in the env var `name`, 0 if it's not set.
+///
+/// Panics if the env var is set but it's not a number from 0 to 1 (e.g. `NaN`).
+fn parse_rate_env_var(name: &str) -> f64 {
+    let rate = parse_env_var(name).unwrap_or(0.0);
+    if !(0.0..=1.0).contains(&rate) {
+        panic!("Invalid {} {}, expected from 0 to 1", name, rate);
+    }
+    rate
+}
+
+/// App services configuration utility to setup the stand-in PokeApi and FunTranslations API services, injecting the
+/// configured latency and faults into their responses.
+pub fn config_stub(cfg: &mut ServiceConfig, config: StubConfig) {
+    let faults = config.clone();
+    cfg.data(config);
+    cfg.service(
+        web::scope("")
+            .wrap_fn(move |req: ServiceRequest, srv| {
+                let latency = faults.latency;
+                let fault = inject_fault(&faults);
+                let resp = match fault {
+                    Some(fault) => Either::Left(ok(req.into_response(fault))),
+                    None => Either::Right(srv.call(req)),
+                };
+                async move {
+                    if latency > Duration::from_millis(0) {
+                        actix_rt::time::delay_for(latency).await;
+                    }
+                    resp.await
+                }
+            })
+            .service(get_pokemon_species)
+            .service(get_pokemon)
+            .service(get_evolution_chain)
+            .service(translate_query)
+            .service(translate_form),
+    );
+}
+
+fn inject_fault(config: &StubConfig) -> Option<HttpResponse> {
+    let mut rng = thread_rng();
+    if rng.gen_bool(config.error_rate.clamp(0.0, 1.0)) {
+        return Some(HttpResponse::InternalServerError().json(json!({
+            "error": { "code": 500, "message": "Internal Server Error (injected by the upstream stub)" }
+        })));
+    }
+    if rng.gen_bool(config.too_many_requests_rate.clamp(0.0, 1.0)) {
+        return Some(HttpResponse::TooManyRequests().json(json!({
+            "error": {
+                "code": 429,
+                "message": "Too Many Requests: Rate limit of 5 requests per hour exceeded (injected by the upstream stub)"
+            }
+        })));
+    }
+    None
+}
+
+/// Stand-in of the PokeApi API service returning a Pokémon species, served as it is from its fixture.
+#[get("/api/v2/pokemon-species/{name}")]
+async fn get_pokemon_species(config: Data<StubConfig>, name: Path<String>) -> HttpResponse {
+    poke_api_fixture_response(&config, "pokemon-species", name.as_str())
+}
+
+/// Stand-in of the PokeApi API service returning a Pokémon (e.g. a form or variety of a species), served as it is
+/// from its fixture.
+#[get("/api/v2/pokemon/{name}")]
+async fn get_pokemon(config: Data<StubConfig>, name: Path<String>) -> HttpResponse {
+    poke_api_fixture_response(&config, "pokemon", name.as_str())
+}
+
+/// Stand-in of the PokeApi API service returning an evolution chain, served as it is from its fixture.
+#[get("/api/v2/evolution-chain/{id}/")]
+async fn get_evolution_chain(config: Data<StubConfig>, id: Path<String>) -> HttpResponse {
+    poke_api_fixture_response(&config, "evolution-chain", id.as_str())
+}
+
+fn poke_api_fixture_response(config: &StubConfig, resource: &str, name: &str) -> HttpResponse {
+    let fixture = config
+        .fixtures_dir
+        .join(resource)
+        .join(format!("{}.json", name));
+    match read_fixture(&fixture) {
+        Some(json) => HttpResponse::Ok()
+            .content_type("application/json; charset=utf-8")
+            .body(json),
+        None => HttpResponse::NotFound().body("Not Found"),
+    }
+}
+
+#[derive(Deserialize)]
+struct TranslateParams {
+    text: String,
+}
+
+/// Dictionary of a FunTranslations style, mapping lowercase words to their translations.
+#[derive(Deserialize)]
+struct StyleDictionary {
+    words: HashMap<String, String>,
+}
+
+/// Stand-in of the FunTranslations API service translating a text sent as query parameter, replacing its words found
+/// in the dictionary of the style and keeping the others as they are.
+#[get("/translate/{style}.json")]
+async fn translate_query(
+    config: Data<StubConfig>,
+    style: Path<String>,
+    query: Query<TranslateParams>,
+) -> HttpResponse {
+    translation_response(&config, style.as_str(), &query.text)
+}
+
+/// Like `translate_query`, but with the text sent as form body.
+#[post("/translate/{style}.json")]
+async fn translate_form(
+    config: Data<StubConfig>,
+    style: Path<String>,
+    form: Form<TranslateParams>,
+) -> HttpResponse {
+    translation_response(&config, style.as_str(), &form.text)
+}
+
+fn translation_response(config: &StubConfig, style: &str, text: &str) -> HttpResponse {
+    let fixture = config
+        .fixtures_dir
+        .join("translate")
+        .join(format!("{}.json", style));
+    let dictionary = match read_fixture(&fixture).and_then(|d| serde_json::from_slice(&d).ok()) {
+        Some(dictionary) => dictionary,
+        None => {
+            return HttpResponse::NotFound().json(json!({
+                "error": { "code": 404, "message": format!("Translator {} not found", style) }
+            }))
+        }
+    };
+
+    HttpResponse::Ok().json(json!({
+        "success": { "total": 1 },
+        "contents": {
+            "translated": translate_words(text, &dictionary),
+            "text": text,
+            "translation": style,
+        }
+    }))
+}
+
+fn translate_words(text: &str, dictionary: &StyleDictionary) -> String {
+    text.split(' ')
+        .map(|token| {
+            // Punctuation around words is kept as it is
+            let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+            match dictionary.words.get(&word.to_lowercase()) {
+                Some(translation) if !word.is_empty() => {
+                    let translation = match word.chars().next() {
+                        Some(first) if first.is_uppercase() => capitalize(translation),
+                        _ => translation.clone(),
+                    };
+                    token.replacen(word, &translation, 1)
+                }
+                _ => token.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn read_fixture(path: &std::path::Path) -> Option<Vec<u8>> {
+    std::fs::read(path).ok()
+}
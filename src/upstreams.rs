@@ -0,0 +1,162 @@
+use crate::fun_translations_client::{FunTranslationsClient, FunTranslationsClientError};
+use crate::poke_api_client::{EvolutionStage, PokeApiClient, PokeApiClientError, SpeciesMetadata};
+use async_trait::async_trait;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Source of Pokémon descriptions used by the API services, `PokeApiClient` by default.
+///
+/// Other sources (e.g. an internal Pokédex DB or a test double) can be plugged in through
+/// `services::UpstreamClients::new`.
+#[async_trait]
+pub trait DescriptionSource: Send + Sync {
+    /// Given a Pokémon name, gets one of its English descriptions.
+    async fn get_description(&self, pokemon_name: &str) -> Result<String, DescriptionSourceError>;
+
+    /// Like `get_description`, but returning also the `SpeciesMetadata` of the Pokémon.
+    ///
+    /// By default the metadata are all unknown, for sources without them.
+    async fn get_description_with_metadata(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<(String, SpeciesMetadata), DescriptionSourceError> {
+        let description = self.get_description(pokemon_name).await?;
+        Ok((description, SpeciesMetadata::default()))
+    }
+
+    /// Given a Pokémon name, gets the evolution chain its species belongs to, starting from its first stage.
+    ///
+    /// By default it fails, for sources without evolution chains.
+    async fn get_evolution_chain(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<EvolutionStage, DescriptionSourceError> {
+        Err(DescriptionSourceError::Other(
+            format!(
+                "Can't get the evolution chain of {}: not supported by the description source",
+                pokemon_name
+            )
+            .into(),
+        ))
+    }
+}
+
+/// Translator of texts in FunTranslations styles used by the API services, `FunTranslationsClient` by default.
+#[async_trait]
+pub trait Translator: Send + Sync {
+    /// Given a text and a FunTranslations style (e.g. `shakespeare` or `yoda`), gets its translation in that style.
+    async fn translate_to(&self, text: &str, style: &str) -> Result<String, TranslatorError>;
+
+    /// Given a text, gets its "Shakespearean" translation.
+    async fn translate(&self, text: &str) -> Result<String, TranslatorError> {
+        self.translate_to(text, "shakespeare").await
+    }
+}
+
+#[async_trait]
+impl DescriptionSource for PokeApiClient {
+    async fn get_description(&self, pokemon_name: &str) -> Result<String, DescriptionSourceError> {
+        self.get_random_description(pokemon_name)
+            .await
+            .map_err(DescriptionSourceError::PokeApi)
+    }
+
+    async fn get_description_with_metadata(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<(String, SpeciesMetadata), DescriptionSourceError> {
+        self.get_random_description_with_metadata(pokemon_name)
+            .await
+            .map_err(DescriptionSourceError::PokeApi)
+    }
+
+    async fn get_evolution_chain(
+        &self,
+        pokemon_name: &str,
+    ) -> Result<EvolutionStage, DescriptionSourceError> {
+        PokeApiClient::get_evolution_chain(self, pokemon_name)
+            .await
+            .map_err(DescriptionSourceError::PokeApi)
+    }
+}
+
+#[async_trait]
+impl Translator for FunTranslationsClient {
+    async fn translate_to(&self, text: &str, style: &str) -> Result<String, TranslatorError> {
+        FunTranslationsClient::translate_to(self, text, style)
+            .await
+            .map_err(TranslatorError::FunTranslations)
+    }
+}
+
+/// Error of a `DescriptionSource`.
+#[derive(Debug)]
+pub enum DescriptionSourceError {
+    PokeApi(PokeApiClientError),
+    /// Unknown Pokémon or Pokémon without English descriptions, for sources other than PokeApi.
+    NotFound(String),
+    /// Any other error of sources other than PokeApi.
+    Other(Box<dyn StdError + Send + Sync>),
+}
+
+impl StdError for DescriptionSourceError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::PokeApi(e) => Some(e),
+            Self::NotFound(_) => None,
+            Self::Other(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+impl Display for DescriptionSourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::PokeApi(e) => Display::fmt(e, f),
+            Self::NotFound(message) => Display::fmt(message, f),
+            Self::Other(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl From<PokeApiClientError> for DescriptionSourceError {
+    fn from(error: PokeApiClientError) -> Self {
+        Self::PokeApi(error)
+    }
+}
+
+/// Error of a `Translator`.
+#[derive(Debug)]
+pub enum TranslatorError {
+    FunTranslations(FunTranslationsClientError),
+    /// Exceeded limits of translators other than FunTranslations.
+    TooManyRequests(String),
+    /// Any other error of translators other than FunTranslations.
+    Other(Box<dyn StdError + Send + Sync>),
+}
+
+impl StdError for TranslatorError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::FunTranslations(e) => Some(e),
+            Self::TooManyRequests(_) => None,
+            Self::Other(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+impl Display for TranslatorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::FunTranslations(e) => Display::fmt(e, f),
+            Self::TooManyRequests(message) => Display::fmt(message, f),
+            Self::Other(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl From<FunTranslationsClientError> for TranslatorError {
+    fn from(error: FunTranslationsClientError) -> Self {
+        Self::FunTranslations(error)
+    }
+}
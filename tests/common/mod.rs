@@ -0,0 +1,216 @@
+//! Test doubles and helpers shared by the integration tests.
+
+// Every test crate uses only some of them
+#![allow(dead_code, unused_imports)]
+
+use actix_web::dev::Server;
+use actix_web::http::{HeaderMap, Method, StatusCode};
+use actix_web::web::{self, Bytes, Query};
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer};
+#[cfg(feature = "server")]
+pub use server::*;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// In-memory log drain output, readable while written by a `Logger`.
+#[derive(Clone, Default)]
+pub struct SharedBuffer(pub Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Empty temporary dir, unique to the given name and to the test process, to be removed by the test.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pokespeare-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("./tests/fixtures/{}", name)).unwrap()
+}
+
+/// Request received by a `MockUpstream`.
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub method: Method,
+    pub path: String,
+    /// Query parameters, together with the form ones of requests with a body.
+    pub params: HashMap<String, String>,
+    pub headers: HeaderMap,
+}
+
+impl MockRequest {
+    fn new(req: &HttpRequest, body: &[u8]) -> Self {
+        let mut params = parse_params(req.query_string());
+        params.extend(parse_params(&String::from_utf8_lossy(body)));
+        Self {
+            method: req.method().clone(),
+            path: req.path().into(),
+            params,
+            headers: req.headers().clone(),
+        }
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+fn parse_params(params: &str) -> HashMap<String, String> {
+    Query::<HashMap<String, String>>::from_query(params)
+        .map(Query::into_inner)
+        .unwrap_or_default()
+}
+
+/// Upstream mock listening on its own port, isolated from the ones of the other tests, to be stopped at the end of the
+/// test.
+pub struct MockUpstream {
+    pub url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    server: Server,
+}
+
+impl MockUpstream {
+    /// Starts a mock answering every request with the response built by the given handler.
+    pub fn start_with(
+        handler: impl Fn(&MockRequest) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        let handler = Arc::new(handler);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let server_requests = requests.clone();
+        let server = HttpServer::new(move || {
+            let (handler, requests) = (handler.clone(), server_requests.clone());
+            App::new().default_service(web::to(move |req: HttpRequest, body: Bytes| {
+                let request = MockRequest::new(&req, &body);
+                let resp = handler(&request);
+                requests.lock().unwrap().push(request);
+                resp
+            }))
+        })
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let url = format!("http://127.0.0.1:{}", server.addrs()[0].port());
+        Self {
+            url,
+            requests,
+            server: server.run(),
+        }
+    }
+
+    /// Starts a mock answering the requests to `method` `path` with the given status and body, and any other request
+    /// with a 404.
+    pub fn start(method: Method, path: &str, status: u16, body: String) -> Self {
+        let path = path.to_string();
+        Self::start_with(move |req| {
+            if req.method == method && req.path == path {
+                HttpResponse::build(StatusCode::from_u16(status).unwrap()).body(body.clone())
+            } else {
+                HttpResponse::NotFound().finish()
+            }
+        })
+    }
+
+    pub fn poke_api(pokemon_name: &str, status: u16, body: &str) -> Self {
+        Self::start(
+            Method::GET,
+            &format!("/api/v2/pokemon-species/{}", pokemon_name),
+            status,
+            body.into(),
+        )
+    }
+
+    pub fn fun_translations(status: u16, body: &str) -> Self {
+        Self::start(
+            Method::GET,
+            "/translate/shakespeare.json",
+            status,
+            body.into(),
+        )
+    }
+
+    /// Requests received so far, in arrival order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn hits(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    pub async fn stop(self) {
+        self.server.stop(true).await;
+    }
+}
+
+/// Stops the PokeApi and FunTranslations API mocks of a test.
+pub async fn stop_upstreams((poke_api, fun_translations): (MockUpstream, MockUpstream)) {
+    poke_api.stop().await;
+    fun_translations.stop().await;
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use actix_web::dev::Server;
+    use actix_web::{App, HttpServer};
+    use async_trait::async_trait;
+    use pokespeare::upstream_stub::{self, StubConfig};
+    use pokespeare::upstreams::{Translator, TranslatorError};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// Translator without HTTP calls, translating any text to uppercase.
+    pub struct UppercaseTranslator;
+
+    #[async_trait]
+    impl Translator for UppercaseTranslator {
+        async fn translate_to(&self, text: &str, _style: &str) -> Result<String, TranslatorError> {
+            Ok(text.to_uppercase())
+        }
+    }
+
+    /// Starts the upstream stub serving the given fixtures, returning it together with its endpoint.
+    pub fn start_stub(fixtures_dir: PathBuf) -> (Server, String) {
+        let config = StubConfig {
+            fixtures_dir,
+            latency: Duration::from_millis(0),
+            error_rate: 0.0,
+            too_many_requests_rate: 0.0,
+        };
+        let server = HttpServer::new(move || {
+            let config = config.clone();
+            App::new().configure(|cfg| upstream_stub::config_stub(cfg, config))
+        })
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let endpoint = format!("http://127.0.0.1:{}", server.addrs()[0].port());
+        (server.run(), endpoint)
+    }
+
+    /// Dir of the fixtures served by the upstream stub by default.
+    pub fn stub_fixtures_dir() -> PathBuf {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures").into()
+    }
+}
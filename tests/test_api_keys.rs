@@ -0,0 +1,230 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::{test, test::TestRequest, App};
+use common::SharedBuffer;
+use pokespeare::api_keys::{ApiKeyConfig, ApiKeys};
+use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode, RateLimitedBy, RequestError};
+use pokespeare::log_drains::Logfmt;
+use pokespeare::log_helpers::{get_discard_logger, Logger};
+use pokespeare::rate_limiting::{RateLimitConfig, RateLimiter};
+use pokespeare::services::{self, SharedState};
+use pokespeare::services_api_models::ShakespeareanDescriptionsBatchApiRequest;
+use slog::{o, Drain};
+use std::sync::Mutex;
+
+#[actix_rt::test]
+async fn test_missing_and_unknown_keys_are_unauthorized() {
+    let mut app = init_app(&get_discard_logger(), api_key(None, None)).await;
+
+    for key in &[None, Some("unknown")] {
+        let resp = call_batch_service(&mut app, *key).await;
+
+        assert_eq!(401, resp.status());
+        assert_eq!(
+            ApiErrorResponseCode::Unauthorized,
+            test::read_body_json::<ApiErrorResponseBody, _>(resp)
+                .await
+                .code
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_documentation_does_not_require_keys() {
+    let mut app = init_app(&get_discard_logger(), api_key(None, None)).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get().uri("/openapi.json").to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+}
+
+#[actix_rt::test]
+async fn test_exceeded_key_rate() {
+    let mut app = init_app(&get_discard_logger(), api_key(Some(1), None)).await;
+
+    let first_resp = call_batch_service(&mut app, Some("s3cr3t")).await;
+    let resp = call_batch_service(&mut app, Some("s3cr3t")).await;
+
+    // The request is authenticated and then rejected by the API service because of the empty batch
+    assert_eq!(400, first_resp.status());
+    assert_eq!(429, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::TooManyRequests,
+            message: "API key of team-roster exceeded its rate of 1 requests per minute".into(),
+            limited_by: Some(RateLimitedBy::Local),
+        },
+        test::read_body_json(resp).await
+    );
+}
+
+#[actix_rt::test]
+async fn test_exceeded_key_daily_quota() {
+    let mut app = init_app(&get_discard_logger(), api_key(None, Some(1))).await;
+
+    let first_resp = call_batch_service(&mut app, Some("s3cr3t")).await;
+    let resp = call_batch_service(&mut app, Some("s3cr3t")).await;
+
+    assert_eq!(400, first_resp.status());
+    assert_eq!(429, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::QuotaExceeded,
+            message: "API key of team-roster exceeded its daily quota of 1 requests".into(),
+            limited_by: Some(RateLimitedBy::Local),
+        },
+        test::read_body_json(resp).await
+    );
+}
+
+#[actix_rt::test]
+async fn test_key_rate_replaces_the_client_rate() {
+    let shared_state = SharedState {
+        api_keys: Some(Data::new(ApiKeys::new(vec![api_key(Some(2), None)]))),
+        rate_limiter: Some(Data::new(rate_limiter(1))),
+        ..SharedState::from_env()
+    };
+    let mut app = init_app_with(&get_discard_logger(), &shared_state).await;
+
+    let first_resp = call_batch_service(&mut app, Some("s3cr3t")).await;
+    let second_resp = call_batch_service(&mut app, Some("s3cr3t")).await;
+
+    assert_eq!(400, first_resp.status());
+    assert_eq!(400, second_resp.status());
+    assert_eq!("2", second_resp.headers().get("ratelimit-limit").unwrap());
+    assert_eq!(
+        "0",
+        second_resp.headers().get("ratelimit-remaining").unwrap()
+    );
+}
+
+#[test]
+fn test_rate_limited_requests_do_not_count_against_quota() {
+    let api_keys = ApiKeys::new(vec![api_key(None, Some(1))]);
+    let exhausted_rate_limiter = rate_limiter(1);
+    exhausted_rate_limiter.check("api_key:team-roster", 1);
+
+    let (decision, rate_limited) = api_keys.limit("team-roster", Some(&exhausted_rate_limiter), 1);
+    let (_, allowed) = api_keys.limit("team-roster", Some(&rate_limiter(1)), 1);
+    let (_, quota_exceeded) = api_keys.limit("team-roster", Some(&rate_limiter(1)), 1);
+
+    assert!(!decision.unwrap().allowed);
+    assert!(matches!(
+        rate_limited,
+        Err(RequestError::TooManyRequests(_))
+    ));
+    assert!(allowed.is_ok());
+    assert!(matches!(
+        quota_exceeded,
+        Err(RequestError::QuotaExceeded(_))
+    ));
+}
+
+#[test]
+fn test_requests_exceeding_quota_take_no_tokens() {
+    let api_keys = ApiKeys::new(vec![api_key(None, Some(1))]);
+    let rate_limiter = rate_limiter(3);
+
+    let (_, allowed) = api_keys.limit("team-roster", Some(&rate_limiter), 1);
+    let (decision, quota_exceeded) = api_keys.limit("team-roster", Some(&rate_limiter), 1);
+
+    assert!(allowed.is_ok());
+    assert!(decision.is_none());
+    assert!(matches!(
+        quota_exceeded,
+        Err(RequestError::QuotaExceeded(_))
+    ));
+    assert_eq!(2, rate_limiter.check("api_key:team-roster", 0).remaining);
+}
+
+#[test]
+fn test_keys_are_authenticated_by_exact_match() {
+    let api_keys = ApiKeys::new(vec![api_key(None, None)]);
+
+    assert_eq!(
+        "team-roster",
+        api_keys.authenticate(Some("s3cr3t")).unwrap()
+    );
+    for key in &["s3cr3", "s3cr3t ", "S3CR3T", ""] {
+        assert!(matches!(
+            api_keys.authenticate(Some(key)),
+            Err(RequestError::Unauthorized(_))
+        ));
+    }
+}
+
+#[actix_rt::test]
+async fn test_key_identity_is_logged() {
+    let buffer = SharedBuffer::default();
+    let log = Logger::root(Mutex::new(Logfmt::new(buffer.clone())).fuse(), o!());
+    let mut app = init_app(&log, api_key(None, None)).await;
+
+    call_batch_service(&mut app, Some("s3cr3t")).await;
+
+    let logs = buffer.contents();
+    assert!(logs.contains("msg=\"API call\""));
+    assert!(logs.contains("status=400"));
+    assert!(logs.contains("api_key=team-roster"));
+}
+
+fn api_key(requests_per_minute: Option<u32>, daily_quota: Option<u64>) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: "team-roster".into(),
+        key: "s3cr3t".into(),
+        requests_per_minute,
+        daily_quota,
+    }
+}
+
+fn rate_limiter(burst: u32) -> RateLimiter {
+    RateLimiter::new(RateLimitConfig {
+        requests_per_minute: 60,
+        burst,
+        trusted_proxies: vec![],
+    })
+}
+
+async fn init_app(
+    log: &Logger,
+    api_key: ApiKeyConfig,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    let shared_state = SharedState {
+        api_keys: Some(Data::new(ApiKeys::new(vec![api_key]))),
+        ..SharedState::from_env()
+    };
+    init_app_with(log, &shared_state).await
+}
+
+async fn init_app_with(
+    log: &Logger,
+    shared_state: &SharedState,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    // Upstreams are never reached: requests are either rejected or invalid
+    std::env::set_var("POKE_API_ENDPOINT", "http://127.0.0.1:1");
+    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", "http://127.0.0.1:1");
+
+    test::init_service(App::new().configure(|cfg| services::config_app(cfg, log, shared_state)))
+        .await
+}
+
+async fn call_batch_service(
+    app: &mut impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error>,
+    key: Option<&str>,
+) -> ServiceResponse {
+    let mut req = TestRequest::post()
+        .uri("/v1/pokemon/batch")
+        .set_json(&ShakespeareanDescriptionsBatchApiRequest { names: vec![] });
+    if let Some(key) = key {
+        req = req.header("x-api-key", key);
+    }
+    test::call_service(app, req.to_request()).await
+}
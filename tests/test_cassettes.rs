@@ -0,0 +1,136 @@
+mod common;
+
+use actix_web::HttpResponse;
+use common::{temp_dir, MockUpstream};
+use pokespeare::cassettes::{Cassettes, Interaction};
+use pokespeare::fun_translations_client::{ApiSecret, FunTranslationsClient};
+use pokespeare::poke_api_client::{PokeApiClient, PokeApiClientError};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// Endpoint never reached by replayed calls.
+const UNREACHABLE_ENDPOINT: &str = "http://127.0.0.1:1";
+
+const MEW_SPECIES: &str = r#"{"flavor_text_entries":[{"flavor_text":"So rare that it\nis still said to\nbe a mirage.","language":{"name":"en"}}]}"#;
+
+#[actix_rt::test]
+async fn test_recorded_calls_are_replayed() {
+    let dir = temp_dir("cassettes-record");
+    let upstream = MockUpstream::start_with(|req| match req.path.as_str() {
+        "/api/v2/pokemon-species/mew" => HttpResponse::Ok()
+            .content_type("application/json")
+            .header("set-cookie", "session=42")
+            .body(MEW_SPECIES),
+        "/translate/shakespeare.json" if req.param("text") == Some("Mew is rare.") => {
+            HttpResponse::Ok()
+                .content_type("application/json")
+                .body(r#"{"contents":{"translated":"Mew is seld."}}"#)
+        }
+        _ => HttpResponse::NotFound().finish(),
+    });
+    let poke_api_client =
+        |endpoint: &str, cassettes| PokeApiClient::new(endpoint).with_cassettes(cassettes);
+    let fun_translations_client = |endpoint: &str, cassettes| {
+        FunTranslationsClient::new(endpoint)
+            .with_api_secret(ApiSecret::new("s3cr3t"))
+            .with_cassettes(cassettes)
+    };
+
+    let recorded_description = poke_api_client(&upstream.url, Cassettes::record(&dir))
+        .get_random_description("mew")
+        .await
+        .unwrap();
+    let recorded_translation = fun_translations_client(&upstream.url, Cassettes::record(&dir))
+        .translate("Mew is rare.")
+        .await
+        .unwrap();
+    let replayed_description = poke_api_client(UNREACHABLE_ENDPOINT, Cassettes::replay(&dir))
+        .get_random_description("mew")
+        .await
+        .unwrap();
+    let replayed_translation =
+        fun_translations_client(UNREACHABLE_ENDPOINT, Cassettes::replay(&dir))
+            .translate("Mew is rare.")
+            .await
+            .unwrap();
+
+    assert_eq!(
+        "So rare that it is still said to be a mirage.",
+        recorded_description
+    );
+    assert_eq!(recorded_description, replayed_description);
+    assert_eq!("Mew is seld.", recorded_translation);
+    assert_eq!(recorded_translation, replayed_translation);
+    let cassettes: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    assert_eq!(2, cassettes.len());
+    assert!(cassettes
+        .iter()
+        .all(|c| !c.contains("s3cr3t") && !c.contains("session")));
+    assert_eq!(2, upstream.hits());
+    upstream.stop().await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_committed_cassettes_are_replayed() {
+    let cassettes = Cassettes::replay(cassettes_dir());
+
+    let description = PokeApiClient::new(UNREACHABLE_ENDPOINT)
+        .with_cassettes(cassettes.clone())
+        .get_random_description("bulbasaur")
+        .await
+        .unwrap();
+    let translation = FunTranslationsClient::new(UNREACHABLE_ENDPOINT)
+        .with_cassettes(cassettes.clone())
+        .translate(&description)
+        .await
+        .unwrap();
+    let not_found = PokeApiClient::new(UNREACHABLE_ENDPOINT)
+        .with_cassettes(cassettes)
+        .get_random_description("missingno")
+        .await
+        .unwrap_err();
+
+    let interaction = Interaction::load(cassettes_dir().join(
+        "get_translate_shakespeare_json_text_A_strange_seed_was_planted_on_its_back_at_birth__86cc15d3.json",
+    ))
+    .unwrap();
+    assert_eq!(
+        interaction.response.body["contents"]["translated"],
+        json!(translation)
+    );
+    assert!(
+        matches!(not_found, PokeApiClientError::RequestError(e) if e.status().map(|s| s.as_u16()) == Some(404))
+    );
+}
+
+#[actix_rt::test]
+async fn test_missing_cassette_is_reported() {
+    let dir = temp_dir("cassettes-missing");
+
+    let error = PokeApiClient::new(UNREACHABLE_ENDPOINT)
+        .with_cassettes(Cassettes::replay(&dir))
+        .get_random_description("mewtwo")
+        .await
+        .unwrap_err();
+
+    // The error names the upstream URL, as if the call reached it
+    assert_eq!(
+        format!(
+            "HTTP status server error (501 Not Implemented) for url ({}/api/v2/pokemon-species/mewtwo)",
+            UNREACHABLE_ENDPOINT
+        ),
+        error.to_string()
+    );
+    assert!(
+        matches!(error, PokeApiClientError::RequestError(e) if e.status().map(|s| s.as_u16()) == Some(501))
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn cassettes_dir() -> PathBuf {
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes").into()
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "server")]
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::{test, test::TestRequest, App};
+use pokespeare::cors::CorsConfig;
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::services::{self, SharedState};
+
+#[actix_rt::test]
+async fn test_allowed_exact_origin() {
+    let mut app = init_app(cors_config(&["https://pokespeare.dev"])).await;
+
+    let resp = call_with_origin(&mut app, "https://pokespeare.dev").await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "https://pokespeare.dev",
+        resp.headers().get("access-control-allow-origin").unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn test_allowed_wildcard_origin() {
+    let mut app = init_app(cors_config(&["https://*.pokespeare.dev"])).await;
+
+    let resp = call_with_origin(&mut app, "https://team.pokespeare.dev").await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "https://team.pokespeare.dev",
+        resp.headers().get("access-control-allow-origin").unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn test_any_origin() {
+    let mut app = init_app(cors_config(&["*"])).await;
+
+    let resp = call_with_origin(&mut app, "https://elsewhere.dev").await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "*",
+        resp.headers().get("access-control-allow-origin").unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn test_rejected_origins() {
+    let mut app = init_app(cors_config(&[
+        "https://pokespeare.dev",
+        "https://*.pokespeare.dev",
+    ]))
+    .await;
+
+    for origin in &[
+        "https://elsewhere.dev",
+        "https://pokespeare.dev.elsewhere.dev",
+        "https://evil.dev/.pokespeare.dev",
+    ] {
+        let resp = call_with_origin(&mut app, origin).await;
+
+        assert_eq!(400, resp.status(), "{} wasn't rejected", origin);
+        assert!(resp.headers().get("access-control-allow-origin").is_none());
+    }
+}
+
+#[actix_rt::test]
+async fn test_preflight_request() {
+    let mut app = init_app(cors_config(&["https://pokespeare.dev"])).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::with_uri("/v1/pokemon/batch")
+            .method(Method::OPTIONS)
+            .header("origin", "https://pokespeare.dev")
+            .header("access-control-request-method", "POST")
+            .header("access-control-request-headers", "content-type")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    let header = |name: &str| resp.headers().get(name).unwrap().to_str().unwrap();
+    assert_eq!(
+        "https://pokespeare.dev",
+        header("access-control-allow-origin")
+    );
+    assert!(header("access-control-allow-methods").contains("POST"));
+    assert!(header("access-control-allow-headers").contains("content-type"));
+    assert_eq!("true", header("access-control-allow-credentials"));
+    assert_eq!("600", header("access-control-max-age"));
+}
+
+#[actix_rt::test]
+async fn test_preflight_request_of_rejected_origin() {
+    let mut app = init_app(cors_config(&["https://pokespeare.dev"])).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::with_uri("/v1/pokemon/batch")
+            .method(Method::OPTIONS)
+            .header("origin", "https://elsewhere.dev")
+            .header("access-control-request-method", "POST")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(400, resp.status());
+    assert!(resp.headers().get("access-control-allow-origin").is_none());
+}
+
+fn cors_config(allowed_origins: &[&str]) -> CorsConfig {
+    CorsConfig {
+        allowed_origins: allowed_origins.iter().map(|&o| o.into()).collect(),
+        allowed_methods: vec![Method::GET, Method::POST],
+        allowed_headers: vec!["content-type".parse().unwrap()],
+        allow_credentials: !allowed_origins.contains(&"*"),
+        max_age_secs: Some(600),
+    }
+}
+
+async fn init_app(
+    cors_config: CorsConfig,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    // Upstreams are never reached: only the documentation API services are called
+    std::env::set_var("POKE_API_ENDPOINT", "http://127.0.0.1:1");
+    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", "http://127.0.0.1:1");
+
+    test::init_service(App::new().wrap(cors_config.cors()).configure(|cfg| {
+        services::config_app(cfg, &get_discard_logger(), &SharedState::from_env())
+    }))
+    .await
+}
+
+async fn call_with_origin(
+    app: &mut impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error>,
+    origin: &str,
+) -> ServiceResponse {
+    let req = TestRequest::get()
+        .uri("/openapi.json")
+        .header("origin", origin)
+        .to_request();
+    test::call_service(app, req).await
+}
@@ -0,0 +1,94 @@
+use pokespeare::descriptions_cache::DescriptionsCache;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn test_persisted_cache_is_restored() {
+    let file = cache_file("restored");
+    let cache = DescriptionsCache::new(Duration::from_secs(60)).with_file(&file);
+    cache.insert("pikachu", "Pikachu that can generate powerful electricity.");
+
+    assert_eq!(Some(1), cache.persist().unwrap());
+
+    let restored_cache = DescriptionsCache::new(Duration::from_secs(60)).with_file(&file);
+    assert_eq!(Some(1), restored_cache.restore().unwrap());
+    assert_eq!(
+        Some("Pikachu that can generate powerful electricity.".into()),
+        restored_cache.get("pikachu")
+    );
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_expired_entries_are_not_restored() {
+    let file = cache_file("expired");
+    let cache = DescriptionsCache::new(Duration::from_secs(60)).with_file(&file);
+    cache.insert("pikachu", "Pikachu that can generate powerful electricity.");
+    cache.persist().unwrap();
+
+    let restored_cache = DescriptionsCache::new(Duration::from_secs(0)).with_file(&file);
+
+    assert_eq!(Some(0), restored_cache.restore().unwrap());
+    assert_eq!(None, restored_cache.get("pikachu"));
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_zero_ttl_disables_the_cache() {
+    let file = cache_file("disabled");
+    let cache = DescriptionsCache::new(Duration::from_secs(0)).with_file(&file);
+    cache.insert("pikachu", "Pikachu that can generate powerful electricity.");
+
+    assert_eq!(None, cache.get("pikachu"));
+    assert_eq!(Some(0), cache.persist().unwrap());
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_least_recently_used_entries_are_evicted() {
+    let cache = DescriptionsCache::new(Duration::from_secs(60)).with_max_entries(2);
+    cache.insert("pikachu", "Pikachu that can generate powerful electricity.");
+    cache.insert("raichu", "Its long tail serves as a ground.");
+    cache.get("pikachu");
+    cache.insert("pichu", "It is not yet skilled at storing electricity.");
+
+    assert_eq!(2, cache.len());
+    assert!(cache.get("pikachu").is_some());
+    assert_eq!(None, cache.get("raichu"));
+    assert!(cache.get("pichu").is_some());
+}
+
+#[test]
+fn test_restored_entries_are_limited_to_the_max() {
+    let file = cache_file("limited");
+    let cache = DescriptionsCache::new(Duration::from_secs(60)).with_file(&file);
+    for name in &["pichu", "pikachu", "raichu"] {
+        cache.insert(name, "Electric Pokémon.");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    cache.persist().unwrap();
+
+    let restored_cache = DescriptionsCache::new(Duration::from_secs(60))
+        .with_file(&file)
+        .with_max_entries(2);
+
+    assert_eq!(Some(2), restored_cache.restore().unwrap());
+    assert_eq!(None, restored_cache.get("pichu"));
+    assert!(restored_cache.get("raichu").is_some());
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_missing_cache_file_is_not_restored() {
+    let cache = DescriptionsCache::new(Duration::from_secs(60)).with_file(cache_file("missing"));
+
+    assert_eq!(None, cache.restore().unwrap());
+}
+
+fn cache_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "pokespeare-test-{}-{}.json",
+        name,
+        std::process::id()
+    ))
+}
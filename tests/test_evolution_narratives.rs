@@ -0,0 +1,117 @@
+use pokespeare::evolution_narratives::narrate;
+use pokespeare::poke_api_client::{EvolutionConditions, EvolutionStage};
+
+#[test]
+fn test_branching_chain() {
+    let eevee = stage(
+        "eevee",
+        vec![],
+        vec![
+            stage("vaporeon", vec![use_item("water-stone")], vec![]),
+            stage(
+                "leafeon",
+                vec![
+                    EvolutionConditions {
+                        trigger: Some("level-up".into()),
+                        location: Some("eterna-forest".into()),
+                        ..EvolutionConditions::default()
+                    },
+                    use_item("leaf-stone"),
+                ],
+                vec![],
+            ),
+            stage(
+                "umbreon",
+                vec![EvolutionConditions {
+                    trigger: Some("level-up".into()),
+                    min_happiness: Some(160),
+                    time_of_day: Some("night".into()),
+                    ..EvolutionConditions::default()
+                }],
+                vec![],
+            ),
+        ],
+    );
+
+    assert_eq!(
+        "Eevee evolves into Vaporeon using a water stone, into Leafeon at eterna forest or using a leaf stone or into \
+        Umbreon with high friendship at night.",
+        narrate(&eevee)
+    );
+}
+
+#[test]
+fn test_multi_stage_chain() {
+    let onix = stage(
+        "onix",
+        vec![],
+        vec![stage(
+            "steelix",
+            vec![EvolutionConditions {
+                trigger: Some("trade".into()),
+                held_item: Some("metal-coat".into()),
+                ..EvolutionConditions::default()
+            }],
+            vec![],
+        )],
+    );
+    let mr_mime = stage(
+        "mime-jr",
+        vec![],
+        vec![stage(
+            "mr-mime",
+            vec![EvolutionConditions {
+                trigger: Some("level-up".into()),
+                known_move: Some("mimic".into()),
+                ..EvolutionConditions::default()
+            }],
+            vec![stage(
+                "mr-rime",
+                vec![EvolutionConditions {
+                    trigger: Some("level-up".into()),
+                    min_level: Some(42),
+                    ..EvolutionConditions::default()
+                }],
+                vec![],
+            )],
+        )],
+    );
+
+    assert_eq!(
+        "Onix evolves into Steelix when traded holding a metal coat.",
+        narrate(&onix)
+    );
+    assert_eq!(
+        "Mime-Jr evolves into Mr-Mime knowing mimic. Mr-Mime evolves into Mr-Rime at level 42.",
+        narrate(&mr_mime)
+    );
+}
+
+#[test]
+fn test_species_without_evolutions() {
+    assert_eq!(
+        "Tauros does not evolve.",
+        narrate(&stage("tauros", vec![], vec![]))
+    );
+}
+
+fn stage(
+    species: &str,
+    conditions: Vec<EvolutionConditions>,
+    evolves_to: Vec<EvolutionStage>,
+) -> EvolutionStage {
+    EvolutionStage {
+        species: species.into(),
+        conditions,
+        evolves_to,
+        stale: false,
+    }
+}
+
+fn use_item(item: &str) -> EvolutionConditions {
+    EvolutionConditions {
+        trigger: Some("use-item".into()),
+        item: Some(item.into()),
+        ..EvolutionConditions::default()
+    }
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use actix_web::http::{Method, StatusCode};
+use actix_web::HttpResponse;
+#[cfg(feature = "server")]
+use actix_web::ResponseError;
+use common::MockUpstream;
+#[cfg(feature = "server")]
+use pokespeare::errors::{ApiError, ApiErrorResponseCode};
+use pokespeare::fun_translations_client::{ApiSecret, FunTranslationsClient, TextChunk};
+use std::collections::HashMap;
+
+#[actix_rt::test]
+async fn test_long_text_is_translated_in_sentence_chunks() {
+    let fun_translations = mock_chunks(&[
+        ("Sparky is fast. Really fast!", 200, "Sparky is swift."),
+        (
+            "Is he the fastest? Surely",
+            200,
+            "Is he the swiftest? Surely",
+        ),
+        (
+            "the fastest Pikachu in town.",
+            200,
+            "the swiftest pikachu in town.",
+        ),
+    ]);
+    let client = FunTranslationsClient::new(&fun_translations.url).with_max_chunk_chars(30);
+
+    let translation = client
+        .translate(
+            "Sparky is fast. Really fast! Is he the fastest? Surely the fastest Pikachu in town.",
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        "Sparky is swift. Is he the swiftest? Surely the swiftest pikachu in town.",
+        translation
+    );
+    fun_translations.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_long_text_costs_a_call_per_chunk() {
+    let fun_translations =
+        mock_chunks(&[("Sparky is fast. Sparky is fast.", 200, "Sparky is swift.")]);
+    let client = FunTranslationsClient::new(&fun_translations.url).with_max_chunk_chars(32);
+    let text = ["Sparky is fast."; 10].join(" ");
+
+    let translation = client.translate(&text).await.unwrap();
+
+    assert_eq!(["Sparky is swift."; 5].join(" "), translation);
+    // Less than `2 * text_chars / max_chunk_chars + 2` calls, as documented
+    assert!(5 < 2 * text.chars().count() / 32 + 2);
+    assert_eq!(5, fun_translations.hits());
+    fun_translations.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_short_text_is_sent_as_query_parameter() {
+    let fun_translations = MockUpstream::start(
+        Method::GET,
+        "/translate/yoda.json",
+        200,
+        translated("Fast, Sparky is."),
+    );
+    let client = FunTranslationsClient::new(&fun_translations.url);
+
+    let translation = client
+        .translate_to("Sparky is fast.", "yoda")
+        .await
+        .unwrap();
+
+    assert_eq!("Fast, Sparky is.", translation);
+    let requests = fun_translations.requests();
+    assert_eq!(1, requests.len());
+    assert_eq!(Some("Sparky is fast."), requests[0].param("text"));
+    fun_translations.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_failed_chunk_is_reported() {
+    let fun_translations = mock_chunks(&[
+        ("Sparky is fast.", 200, "Sparky is swift."),
+        ("Really fast!", 503, ""),
+    ]);
+    let client = FunTranslationsClient::new(&fun_translations.url).with_max_chunk_chars(15);
+
+    let error = client
+        .translate("Sparky is fast. Really fast!")
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        Some(TextChunk {
+            number: 2,
+            count: 2
+        }),
+        error.chunk
+    );
+    assert!(error.to_string().starts_with("chunk 2 of 2: "));
+    fun_translations.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_api_secret_is_sent() {
+    let fun_translations = MockUpstream::fun_translations(200, &translated("Raichu is swift."));
+    let client = FunTranslationsClient::new(&fun_translations.url)
+        .with_api_secret(ApiSecret::new("s3cr3t\n"));
+
+    let translation = client.translate("Raichu is fast.").await.unwrap();
+
+    assert_eq!("Raichu is swift.", translation);
+    assert_eq!(
+        Some("s3cr3t"),
+        fun_translations.requests()[0].header("x-funtranslations-api-secret")
+    );
+    fun_translations.stop().await;
+}
+
+#[cfg(feature = "server")]
+#[actix_rt::test]
+async fn test_invalid_api_secret_is_reported() {
+    let fun_translations = MockUpstream::fun_translations(401, "");
+    let client =
+        FunTranslationsClient::new(&fun_translations.url).with_api_secret(ApiSecret::new("wr0ng"));
+
+    let error = client.translate("Raichu is fast.").await.unwrap_err();
+
+    assert_eq!(500, error.status_code());
+    assert_eq!(
+        ApiErrorResponseCode::FunTranslationsUnauthorized,
+        error.api_error_response_body().code
+    );
+    fun_translations.stop().await;
+}
+
+#[test]
+fn test_api_secret_is_redacted() {
+    assert_eq!(
+        "ApiSecret(<redacted>)",
+        format!("{:?}", ApiSecret::new("s3cr3t"))
+    );
+}
+
+/// Starts a FunTranslations API mock translating each of the given text chunks, sent as form parameter, with the
+/// given status and translation, and answering any other request with a 404.
+fn mock_chunks(chunks: &[(&str, u16, &str)]) -> MockUpstream {
+    let chunks = chunks
+        .iter()
+        .map(|&(chunk, status, translation)| (chunk.to_string(), (status, translated(translation))))
+        .collect::<HashMap<_, _>>();
+    MockUpstream::start_with(move |req| {
+        let chunk = req
+            .param("text")
+            .filter(|_| req.method == Method::POST && req.path == "/translate/shakespeare.json")
+            .and_then(|text| chunks.get(text));
+        match chunk {
+            Some((status, translation)) => {
+                HttpResponse::build(StatusCode::from_u16(*status).unwrap())
+                    .body(translation.clone())
+            }
+            None => HttpResponse::NotFound().finish(),
+        }
+    })
+}
+
+fn translated(translation: &str) -> String {
+    serde_json::json!({ "contents": { "translated": translation } }).to_string()
+}
@@ -1,235 +1,743 @@
-use actix_web::App;
-use actix_web::{dev::ServiceResponse, test, test::TestRequest};
-use mockito::{mock, Matcher};
-use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode};
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::{test, test::TestRequest, App};
+use async_trait::async_trait;
+use common::{
+    fixture, start_stub, stop_upstreams, stub_fixtures_dir, MockUpstream, UppercaseTranslator,
+};
+use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode, RateLimitedBy};
 use pokespeare::fun_translations_client::FunTranslationsClient;
+use pokespeare::log_helpers::get_discard_logger;
 use pokespeare::poke_api_client::PokeApiClient;
-use pokespeare::services;
-use pokespeare::services_api_models::ShakespeareanDescriptionApiResponse;
+use pokespeare::services::{self, SharedState, UpstreamClients};
+use pokespeare::services_api_models::{ShakespeareanDescriptionApiResponse, SpeciesApiResponse};
+use pokespeare::upstreams::{DescriptionSource, DescriptionSourceError};
 
 #[actix_rt::test]
 async fn test_happy_path() {
     let pokemon_name = "bulbasaur";
+    let upstreams = mock_upstreams(pokemon_name);
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
-    )
-    .with_status(200)
-    .with_body(std::fs::read_to_string("./tests/fixtures/poke_api_valid_response.json").unwrap())
-    .create();
-    let _fun_translations_mock = mock("GET", "/translate/shakespeare.json")
-        .match_query(Matcher::Regex("text=.*".into()))
-        .with_status(200)
-        .with_body(
-            std::fs::read_to_string("./tests/fixtures/fun_translations_valid_response.json")
-                .unwrap(),
-        )
-        .create();
-
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(200, resp.status());
+    assert!(resp.headers().get("deprecation").is_none());
     assert_eq!(
         ShakespeareanDescriptionApiResponse {
             name: pokemon_name.into(),
             description: "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.".into(),
+            species_name: None,
+            stale: false,
+            species: None,
         },
         test::read_body_json(resp).await
     );
+    stop_upstreams(upstreams).await;
 }
 
 #[actix_rt::test]
-async fn test_poke_api_returns_status_code_different_from_200() {
+async fn test_unversioned_path_is_a_deprecated_alias() {
     let pokemon_name = "bulbasaur";
+    let upstreams = mock_upstreams(pokemon_name);
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
+    let resp = call_service(
+        TestRequest::get().uri(&format!("/pokemon/{}", pokemon_name)),
+        &upstreams,
     )
-    .with_status(404)
-    .create();
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!("true", resp.headers().get("deprecation").unwrap());
+    assert_eq!(
+        services::UNVERSIONED_API_SUNSET,
+        resp.headers().get("sunset").unwrap()
+    );
+    assert_eq!(
+        "</v1/pokemon/bulbasaur>; rel=\"successor-version\"",
+        resp.headers().get("link").unwrap()
+    );
+    assert_eq!(
+        ShakespeareanDescriptionApiResponse {
+            name: pokemon_name.into(),
+            description: "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.".into(),
+            species_name: None,
+            stale: false,
+            species: None,
+        },
+        test::read_body_json(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
 
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+#[actix_rt::test]
+async fn test_poke_api_returns_status_code_different_from_200() {
+    let pokemon_name = "bulbasaur";
+    let upstreams = (
+        MockUpstream::poke_api(pokemon_name, 404, ""),
+        MockUpstream::fun_translations(200, &fixture("fun_translations_valid_response.json")),
+    );
+
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(404, resp.status());
     assert_eq!(
         ApiErrorResponseBody {
             code: ApiErrorResponseCode::PokeApiError,
-            message: "HTTP status client error (404 Not Found) for url (http://127.0.0.1:1234/api/v2/pokemon-species/bulbasaur)".into(),
+            message: format!(
+                "HTTP status client error (404 Not Found) for url ({}/api/v2/pokemon-species/bulbasaur)",
+                upstreams.0.url
+            ),
+            limited_by: None,
         },
         test::read_body_json(resp).await
     );
+    assert_eq!(0, upstreams.1.hits());
+    stop_upstreams(upstreams).await;
 }
 
 #[actix_rt::test]
 async fn test_poke_apis_returns_200_with_unexpected_body() {
     let pokemon_name = "bulbasaur";
+    let upstreams = (
+        MockUpstream::poke_api(pokemon_name, 200, "That's the body you're looking for..."),
+        MockUpstream::fun_translations(200, &fixture("fun_translations_valid_response.json")),
+    );
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
-    )
-    .with_status(200)
-    .with_body("That's the body you're looking for...")
-    .create();
-
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(500, resp.status());
     assert_eq!(
         ApiErrorResponseBody {
             code: ApiErrorResponseCode::PokeApiError,
             message: "error decoding response body: expected value at line 1 column 1".into(),
+            limited_by: None,
         },
         test::read_body_json(resp).await
     );
+    stop_upstreams(upstreams).await;
 }
 
 #[actix_rt::test]
 async fn test_poke_apis_returns_200_without_a_traslatable_description() {
     let pokemon_name = "bulbasaur";
+    let upstreams = (
+        MockUpstream::poke_api(
+            pokemon_name,
+            200,
+            &fixture("poke_api_not_translatable_description_response.json"),
+        ),
+        MockUpstream::fun_translations(200, &fixture("fun_translations_valid_response.json")),
+    );
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
-    )
-    .with_status(200)
-    .with_body(
-        std::fs::read_to_string(
-            "./tests/fixtures/poke_api_not_translatable_description_response.json",
-        )
-        .unwrap(),
-    )
-    .create();
-
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(404, resp.status());
     assert_eq!(
         ApiErrorResponseBody {
             code: ApiErrorResponseCode::TranslatableDescriptionNotFound,
-            message: "No \'en\' descripiton found when calling PokeApi URL \"http://127.0.0.1:1234/api/v2/pokemon-species/bulbasaur\"".into(),
+            message: format!(
+                "No \'en\' descripiton found when calling PokeApi URL \"{}/api/v2/pokemon-species/bulbasaur\"",
+                upstreams.0.url
+            ),
+            limited_by: None,
         },
         test::read_body_json(resp).await
     );
+    stop_upstreams(upstreams).await;
 }
 
 #[actix_rt::test]
 async fn test_exceeded_limits_of_fun_translations_api_calls() {
     let pokemon_name = "bulbasaur";
+    let upstreams = (
+        MockUpstream::poke_api(pokemon_name, 200, &fixture("poke_api_valid_response.json")),
+        MockUpstream::fun_translations(429, &fixture("fun_translations_valid_response.json")),
+    );
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
-    )
-    .with_status(200)
-    .with_body(std::fs::read_to_string("./tests/fixtures/poke_api_valid_response.json").unwrap())
-    .create();
-
-    let _fun_translations_mock = mock("GET", "/translate/shakespeare.json")
-        .match_query(Matcher::Regex("text=.*".into()))
-        .with_status(429)
-        .with_body(
-            std::fs::read_to_string("./tests/fixtures/fun_translations_valid_response.json")
-                .unwrap(),
-        )
-        .create();
-
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(429, resp.status());
     assert_eq!(
         ApiErrorResponseBody {
             code: ApiErrorResponseCode::TooManyRequests,
-            message: "HTTP status client error (429 Too Many Requests) for url (http://127.0.0.1:1234/translate/shakespeare.json?text=A+strange+seed+was+planted+on+its+back+at+birth.+The+plant+sprouts+and+grows+with+this+POK%C3%A9MON.)".into(),
+            message: format!(
+                "HTTP status client error (429 Too Many Requests) for url ({}/translate/shakespeare.json?text=A+strange+seed+was+planted+on+its+back+at+birth.+The+plant+sprouts+and+grows+with+this+POK%C3%A9MON.)",
+                upstreams.1.url
+            ),
+            limited_by: Some(RateLimitedBy::Upstream),
         },
         test::read_body_json(resp).await
     );
+    stop_upstreams(upstreams).await;
 }
 
 #[actix_rt::test]
 async fn test_fun_translations_returns_status_code_different_from_200() {
     let pokemon_name = "bulbasaur";
+    let upstreams = (
+        MockUpstream::poke_api(pokemon_name, 200, &fixture("poke_api_valid_response.json")),
+        MockUpstream::fun_translations(503, &fixture("fun_translations_valid_response.json")),
+    );
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
-    )
-    .with_status(200)
-    .with_body(std::fs::read_to_string("./tests/fixtures/poke_api_valid_response.json").unwrap())
-    .create();
-
-    let _fun_translations_mock = mock("GET", "/translate/shakespeare.json")
-        .match_query(Matcher::Regex("text=.*".into()))
-        .with_status(503)
-        .with_body(
-            std::fs::read_to_string("./tests/fixtures/fun_translations_valid_response.json")
-                .unwrap(),
-        )
-        .create();
-
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(503, resp.status());
     assert_eq!(
         ApiErrorResponseBody {
             code: ApiErrorResponseCode::FunTranslationsError,
-            message: "HTTP status server error (503 Service Unavailable) for url (http://127.0.0.1:1234/translate/shakespeare.json?text=A+strange+seed+was+planted+on+its+back+at+birth.+The+plant+sprouts+and+grows+with+this+POK%C3%A9MON.)".into(),
+            message: format!(
+                "HTTP status server error (503 Service Unavailable) for url ({}/translate/shakespeare.json?text=A+strange+seed+was+planted+on+its+back+at+birth.+The+plant+sprouts+and+grows+with+this+POK%C3%A9MON.)",
+                upstreams.1.url
+            ),
+            limited_by: None,
         },
         test::read_body_json(resp).await
     );
+    stop_upstreams(upstreams).await;
 }
 
 #[actix_rt::test]
 async fn test_fun_translations_returns_200_with_unexpected_body() {
     let pokemon_name = "bulbasaur";
+    let upstreams = (
+        MockUpstream::poke_api(pokemon_name, 200, &fixture("poke_api_valid_response.json")),
+        MockUpstream::fun_translations(200, "That's the body you're looking for..."),
+    );
 
-    let _poke_api_mock = mock(
-        "GET",
-        format!("/api/v2/pokemon-species/{}", pokemon_name).as_str(),
-    )
-    .with_status(200)
-    .with_body(std::fs::read_to_string("./tests/fixtures/poke_api_valid_response.json").unwrap())
-    .create();
-
-    let _fun_translations_mock = mock("GET", "/translate/shakespeare.json")
-        .match_query(Matcher::Regex("text=.*".into()))
-        .with_status(200)
-        .with_body("That's the body you're looking for...")
-        .create();
-
-    let resp = call_get_shakespearean_description_service(pokemon_name).await;
+    let resp = call_get_shakespearean_description_service(pokemon_name, &upstreams).await;
 
     assert_eq!(500, resp.status());
     assert_eq!(
         ApiErrorResponseBody {
             code: ApiErrorResponseCode::FunTranslationsError,
             message: "error decoding response body: expected value at line 1 column 1".into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_description_as_plain_text() {
+    let upstreams = mock_upstreams("bulbasaur");
+
+    let resp = call_service_accepting("bulbasaur", "text/plain", &upstreams).await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "text/plain; charset=utf-8",
+        resp.headers().get("content-type").unwrap()
+    );
+    assert_eq!("Accept", resp.headers().get("vary").unwrap());
+    assert_eq!(
+        "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.",
+        test::read_body(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_description_as_html_embed() {
+    let upstreams = mock_upstreams("bulbasaur");
+
+    let resp = call_service_accepting("bulbasaur", "text/html", &upstreams).await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "text/html; charset=utf-8",
+        resp.headers().get("content-type").unwrap()
+    );
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains("<blockquote>A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.</blockquote>"));
+    assert!(body.contains("<figcaption>bulbasaur</figcaption>"));
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_description_as_xml_honoring_quality_values() {
+    let upstreams = mock_upstreams("bulbasaur");
+
+    let resp =
+        call_service_accepting("bulbasaur", "text/html;q=0.5, application/xml", &upstreams).await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "application/xml; charset=utf-8",
+        resp.headers().get("content-type").unwrap()
+    );
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<shakespeareanDescription><name>bulbasaur</name><description>A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.</description></shakespeareanDescription>\n",
+        test::read_body(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_unsupported_media_type_is_not_acceptable() {
+    let upstreams = mock_upstreams("bulbasaur");
+
+    let resp = call_service_accepting("bulbasaur", "image/png", &upstreams).await;
+
+    assert_eq!(406, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::NotAcceptable,
+            message:
+                "Available media types: application/json, text/plain, text/html, application/xml"
+                    .into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_cached_description_is_not_modified_for_matching_etag() {
+    let upstreams = mock_upstreams("bulbasaur");
+    let mut app = init_app(&upstreams).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get().uri("/v1/pokemon/bulbasaur").to_request(),
+    )
+    .await;
+    let etag = resp.headers().get("etag").unwrap().clone();
+    let not_modified_resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur")
+            .header("if-none-match", etag.clone())
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert!(etag.to_str().unwrap().starts_with('"'));
+    assert_eq!(
+        "public, max-age=3600",
+        resp.headers().get("cache-control").unwrap()
+    );
+    assert_eq!(304, not_modified_resp.status());
+    assert_eq!(&etag, not_modified_resp.headers().get("etag").unwrap());
+    assert!(test::read_body(not_modified_resp).await.is_empty());
+    assert_eq!(1, upstreams.0.hits());
+    assert_eq!(1, upstreams.1.hits());
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_apps_call_their_own_upstreams() {
+    let first_upstreams = mock_upstreams("bulbasaur");
+    let second_upstreams = mock_upstreams("bulbasaur");
+
+    let first_resp =
+        call_get_shakespearean_description_service("bulbasaur", &first_upstreams).await;
+    let second_resp =
+        call_get_shakespearean_description_service("bulbasaur", &second_upstreams).await;
+
+    assert_eq!(200, first_resp.status());
+    assert_eq!(200, second_resp.status());
+    assert_eq!((1, 1), (first_upstreams.0.hits(), first_upstreams.1.hits()));
+    assert_eq!(
+        (1, 1),
+        (second_upstreams.0.hits(), second_upstreams.1.hits())
+    );
+    stop_upstreams(first_upstreams).await;
+    stop_upstreams(second_upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_custom_description_source_and_translator() {
+    let mut app = init_app_with(UpstreamClients::new(Pokedex, UppercaseTranslator)).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get().uri("/v1/pokemon/pikachu").to_request(),
+    )
+    .await;
+    let not_found_resp = test::call_service(
+        &mut app,
+        TestRequest::get().uri("/v1/pokemon/missingno").to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        ShakespeareanDescriptionApiResponse {
+            name: "pikachu".into(),
+            description: "IT KEEPS ITS TAIL RAISED TO MONITOR ITS SURROUNDINGS.".into(),
+            species_name: None,
+            stale: false,
+            species: None,
+        },
+        test::read_body_json(resp).await
+    );
+    assert_eq!(404, not_found_resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::TranslatableDescriptionNotFound,
+            message: "No missingno in the Pokédex".into(),
+            limited_by: None,
+        },
+        test::read_body_json(not_found_resp).await
+    );
+}
+
+#[actix_rt::test]
+async fn test_included_species_data() {
+    let upstreams = (
+        MockUpstream::poke_api("bulbasaur", 200, &fixture("poke_api_species_response.json")),
+        MockUpstream::fun_translations(200, &fixture("fun_translations_valid_response.json")),
+    );
+    let mut app = init_app(&upstreams).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=genus,generation,color,habitat,legendary,mythical,pokedex_number")
+            .to_request(),
+    )
+    .await;
+    let cached_resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=pokedex_number")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        ShakespeareanDescriptionApiResponse {
+            name: "bulbasaur".into(),
+            description: "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.".into(),
+            species_name: None,
+            stale: false,
+            species: Some(SpeciesApiResponse {
+                genus: Some("Seed Pokémon".into()),
+                shakespearean_genus: None,
+                generation: Some("generation-i".into()),
+                color: Some("green".into()),
+                habitat: Some("grassland".into()),
+                is_legendary: Some(false),
+                is_mythical: Some(false),
+                pokedex_number: Some(1),
+            }),
+        },
+        test::read_body_json(resp).await
+    );
+    assert_eq!(200, cached_resp.status());
+    assert_eq!(
+        serde_json::json!({ "pokedex_number": 1 }),
+        test::read_body_json::<serde_json::Value, _>(cached_resp).await["species"]
+    );
+    // Species data are not cached, unlike the description
+    assert_eq!(2, upstreams.0.hits());
+    assert_eq!(1, upstreams.1.hits());
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_shakespearean_genus_is_translated_on_demand() {
+    let poke_api =
+        MockUpstream::poke_api("bulbasaur", 200, &fixture("poke_api_species_response.json"));
+    let clients = UpstreamClients::new(PokeApiClient::new(&poke_api.url), UppercaseTranslator);
+    let mut app = init_app_with(clients).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=shakespearean_genus")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        ShakespeareanDescriptionApiResponse {
+            name: "bulbasaur".into(),
+            description: "A STRANGE SEED WAS PLANTED ON ITS BACK AT BIRTH. THE PLANT SPROUTS AND GROWS WITH THIS POKÉMON.".into(),
+            species_name: None,
+            stale: false,
+            species: Some(SpeciesApiResponse {
+                shakespearean_genus: Some("SEED POKÉMON".into()),
+                ..SpeciesApiResponse::default()
+            }),
+        },
+        test::read_body_json(resp).await
+    );
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_species_data_as_xml() {
+    let poke_api =
+        MockUpstream::poke_api("bulbasaur", 200, &fixture("poke_api_species_response.json"));
+    let clients = UpstreamClients::new(PokeApiClient::new(&poke_api.url), UppercaseTranslator);
+    let mut app = init_app_with(clients).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=shakespearean_genus,legendary,pokedex_number")
+            .header("accept", "application/xml")
+            .to_request(),
+    )
+    .await;
+    let plain_text_resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=genus")
+            .header("accept", "text/plain")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<shakespeareanDescription><name>bulbasaur</name><description>A STRANGE SEED WAS PLANTED ON ITS BACK AT BIRTH. THE PLANT SPROUTS AND GROWS WITH THIS POKÉMON.</description><species><shakespeareanGenus>SEED POKÉMON</shakespeareanGenus><isLegendary>false</isLegendary><pokedexNumber>1</pokedexNumber></species></shakespeareanDescription>\n",
+        test::read_body(resp).await
+    );
+    assert_eq!(406, plain_text_resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::NotAcceptable,
+            message: "Available media types: application/json, application/xml".into(),
+            limited_by: None,
+        },
+        test::read_body_json(plain_text_resp).await
+    );
+    assert_eq!(1, poke_api.hits());
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_invalid_name_is_rejected_before_the_cache() {
+    let poke_api =
+        MockUpstream::poke_api("bulbasaur", 200, &fixture("poke_api_species_response.json"));
+    let clients = UpstreamClients::new(PokeApiClient::new(&poke_api.url), UppercaseTranslator);
+    let mut app = init_app_with(clients).await;
+
+    // Caches the Shakespearean genus of Bulbasaur
+    let genus_resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=shakespearean_genus")
+            .to_request(),
+    )
+    .await;
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur%23genus")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, genus_resp.status());
+    assert_eq!(400, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::InvalidRequest,
+            message: "Invalid Pokémon name \"bulbasaur#genus\", expected lowercase letters, digits and dashes only".into(),
+            limited_by: None,
         },
         test::read_body_json(resp).await
     );
+    assert_eq!(1, poke_api.hits());
+    poke_api.stop().await;
 }
 
-fn set_up_mocks() -> (PokeApiClient, FunTranslationsClient) {
-    let mock_server_url = mockito::server_url();
+#[actix_rt::test]
+async fn test_form_gets_the_description_of_its_species() {
+    let (stub, endpoint) = start_stub(stub_fixtures_dir());
+    let clients = UpstreamClients::new(PokeApiClient::new(&endpoint), UppercaseTranslator);
+    let mut app = init_app_with(clients).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/charizard-mega-x")
+            .to_request(),
+    )
+    .await;
+    let cached_resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/charizard-mega-x")
+            .to_request(),
+    )
+    .await;
+    let species_resp = test::call_service(
+        &mut app,
+        TestRequest::get().uri("/v1/pokemon/charizard").to_request(),
+    )
+    .await;
 
-    std::env::set_var("POKE_API_ENDPOINT", &mock_server_url);
-    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", &mock_server_url);
+    assert_eq!(200, resp.status());
+    let description: ShakespeareanDescriptionApiResponse = test::read_body_json(resp).await;
+    assert_eq!("charizard-mega-x", description.name);
+    assert_eq!(Some("charizard".into()), description.species_name);
+    assert!(
+        [
+            "SPITS FIRE THAT IS HOT ENOUGH TO MELT BOULDERS. KNOWN TO CAUSE FOREST FIRES UNINTENTIONALLY.",
+            "IT FLIES AROUND THE SKY IN SEARCH OF POWERFUL OPPONENTS."
+        ]
+        .contains(&description.description.as_str()),
+        "{:?}",
+        description
+    );
+    assert_eq!(200, cached_resp.status());
+    assert_eq!(description, test::read_body_json(cached_resp).await);
+    assert_eq!(200, species_resp.status());
+    let species_description: ShakespeareanDescriptionApiResponse =
+        test::read_body_json(species_resp).await;
+    assert_eq!(None, species_description.species_name);
+    stub.stop(true).await;
+}
+
+#[actix_rt::test]
+async fn test_form_species_is_named_in_xml_and_html() {
+    let (stub, endpoint) = start_stub(stub_fixtures_dir());
+    let clients = UpstreamClients::new(PokeApiClient::new(&endpoint), UppercaseTranslator);
+    let mut app = init_app_with(clients).await;
+    let call_accepting = |accept: &str| {
+        TestRequest::get()
+            .uri("/v1/pokemon/charizard-mega-x")
+            .header("accept", accept)
+            .to_request()
+    };
+
+    let xml_resp = test::call_service(&mut app, call_accepting("application/xml")).await;
+    let html_resp = test::call_service(&mut app, call_accepting("text/html")).await;
+
+    assert_eq!(200, xml_resp.status());
+    let xml = String::from_utf8(test::read_body(xml_resp).await.to_vec()).unwrap();
+    assert!(
+        xml.contains("<name>charizard-mega-x</name>")
+            && xml.contains(
+                "</description><speciesName>charizard</speciesName></shakespeareanDescription>"
+            ),
+        "{}",
+        xml
+    );
+    assert_eq!(200, html_resp.status());
+    let html = String::from_utf8(test::read_body(html_resp).await.to_vec()).unwrap();
+    assert!(
+        html.contains(
+            "<figcaption>charizard-mega-x <span class=\"species-name\">(charizard)</span></figcaption>"
+        ),
+        "{}",
+        html
+    );
+    stub.stop(true).await;
+}
 
+#[actix_rt::test]
+async fn test_unknown_include_is_invalid() {
+    let upstreams = mock_upstreams("bulbasaur");
+
+    let resp = call_service(
+        TestRequest::get().uri("/v1/pokemon/bulbasaur?include=genus,weight"),
+        &upstreams,
+    )
+    .await;
+
+    assert_eq!(400, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::InvalidRequest,
+            message: "Unknown include \"weight\", expected any of: genus, shakespearean_genus, generation, color, habitat, legendary, mythical, pokedex_number".into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+    assert_eq!(0, upstreams.0.hits());
+    stop_upstreams(upstreams).await;
+}
+
+/// Description source without HTTP calls, knowing only Pikachu.
+struct Pokedex;
+
+#[async_trait]
+impl DescriptionSource for Pokedex {
+    async fn get_description(&self, pokemon_name: &str) -> Result<String, DescriptionSourceError> {
+        match pokemon_name {
+            "pikachu" => Ok("It keeps its tail raised to monitor its surroundings.".into()),
+            _ => Err(DescriptionSourceError::NotFound(format!(
+                "No {} in the Pokédex",
+                pokemon_name
+            ))),
+        }
+    }
+}
+
+fn mock_upstreams(pokemon_name: &str) -> (MockUpstream, MockUpstream) {
     (
-        PokeApiClient::new(&mock_server_url),
-        FunTranslationsClient::new(&mock_server_url),
+        MockUpstream::poke_api(pokemon_name, 200, &fixture("poke_api_valid_response.json")),
+        MockUpstream::fun_translations(200, &fixture("fun_translations_valid_response.json")),
     )
 }
 
-async fn call_get_shakespearean_description_service(pokemon_name: &str) -> ServiceResponse {
-    let (poke_api_client, fun_translations_client) = set_up_mocks();
-    let mut app = test::init_service(App::new().configure(services::config_app)).await;
-    let req = TestRequest::get()
-        .uri(&format!("/pokemon/{}", pokemon_name))
-        .data(poke_api_client)
-        .data(fun_translations_client)
-        .to_request();
-    test::call_service(&mut app, req).await
+async fn call_get_shakespearean_description_service(
+    pokemon_name: &str,
+    upstreams: &(MockUpstream, MockUpstream),
+) -> ServiceResponse {
+    call_service(
+        TestRequest::get().uri(&format!("/v1/pokemon/{}", pokemon_name)),
+        upstreams,
+    )
+    .await
+}
+
+async fn call_service_accepting(
+    pokemon_name: &str,
+    accept: &str,
+    upstreams: &(MockUpstream, MockUpstream),
+) -> ServiceResponse {
+    call_service(
+        TestRequest::get()
+            .uri(&format!("/v1/pokemon/{}", pokemon_name))
+            .header("accept", accept),
+        upstreams,
+    )
+    .await
+}
+
+async fn call_service(
+    req: TestRequest,
+    upstreams: &(MockUpstream, MockUpstream),
+) -> ServiceResponse {
+    let mut app = init_app(upstreams).await;
+    test::call_service(&mut app, req.to_request()).await
+}
+
+async fn init_app(
+    (poke_api, fun_translations): &(MockUpstream, MockUpstream),
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    let clients = UpstreamClients::new(
+        PokeApiClient::new(&poke_api.url),
+        FunTranslationsClient::new(&fun_translations.url),
+    );
+    init_app_with(clients).await
+}
+
+async fn init_app_with(
+    clients: UpstreamClients,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(
+            cfg,
+            &get_discard_logger(),
+            &SharedState::from_env(),
+            clients,
+        )
+    }))
+    .await
 }
@@ -0,0 +1,289 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::{test, test::TestRequest, App};
+use async_trait::async_trait;
+use common::{start_stub, stub_fixtures_dir, temp_dir};
+use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode};
+use pokespeare::fun_translations_client::FunTranslationsClient;
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::poke_api_client::{EvolutionStage, PokeApiClient};
+use pokespeare::services::{self, SharedState, UpstreamClients};
+use pokespeare::services_api_models::{
+    EvolutionApiResponse, EvolutionConditionsApiResponse, EvolutionStageApiResponse,
+};
+use pokespeare::upstreams::{
+    DescriptionSource, DescriptionSourceError, Translator, TranslatorError,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[actix_rt::test]
+async fn test_happy_path() {
+    let (server, endpoint) = start_stub(stub_fixtures_dir());
+    let mut app = init_app_with(UpstreamClients::new(
+        PokeApiClient::new(&endpoint),
+        FunTranslationsClient::new(&endpoint),
+    ))
+    .await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/pikachu/evolution")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        EvolutionApiResponse {
+            name: "pikachu".into(),
+            chain: EvolutionStageApiResponse {
+                species: "pichu".into(),
+                conditions: vec![],
+                evolves_to: vec![EvolutionStageApiResponse {
+                    species: "pikachu".into(),
+                    conditions: vec![EvolutionConditionsApiResponse {
+                        trigger: Some("level-up".into()),
+                        min_happiness: Some(220),
+                        ..EvolutionConditionsApiResponse::default()
+                    }],
+                    evolves_to: vec![EvolutionStageApiResponse {
+                        species: "raichu".into(),
+                        conditions: vec![EvolutionConditionsApiResponse {
+                            trigger: Some("use-item".into()),
+                            item: Some("thunder-stone".into()),
+                            ..EvolutionConditionsApiResponse::default()
+                        }],
+                        evolves_to: vec![],
+                    }],
+                }],
+            },
+            narrative: "Pichu evolves into Pikachu with high friendship. Pikachu evolves into Raichu using a thunder stone.".into(),
+        },
+        test::read_body_json(resp).await
+    );
+    server.stop(true).await;
+}
+
+#[actix_rt::test]
+async fn test_narrative_translation_is_cached() {
+    let (server, endpoint) = start_stub(stub_fixtures_dir());
+    let translations = Arc::new(AtomicUsize::new(0));
+    let mut app = init_app_with(UpstreamClients::new(
+        PokeApiClient::new(&endpoint),
+        CountingTranslator(translations.clone()),
+    ))
+    .await;
+
+    for _ in 0..2 {
+        let resp = test::call_service(
+            &mut app,
+            TestRequest::get()
+                .uri("/v1/pokemon/bulbasaur/evolution")
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(200, resp.status());
+        let evolution: EvolutionApiResponse = test::read_body_json(resp).await;
+        assert_eq!(
+            "BULBASAUR EVOLVES INTO IVYSAUR AT LEVEL 16. IVYSAUR EVOLVES INTO VENUSAUR AT LEVEL 32.",
+            evolution.narrative
+        );
+    }
+    assert_eq!(1, translations.load(Ordering::SeqCst));
+    server.stop(true).await;
+}
+
+#[actix_rt::test]
+async fn test_narrative_translation_is_cached_by_chain() {
+    let dir = temp_dir("evolution-same-chain");
+    for resource in &["pokemon-species", "evolution-chain"] {
+        std::fs::create_dir_all(dir.join(resource)).unwrap();
+    }
+    let species = stub_fixtures_dir().join("pokemon-species/bulbasaur.json");
+    for name in &["bulbasaur", "ivysaur", "venusaur"] {
+        std::fs::copy(&species, dir.join(format!("pokemon-species/{}.json", name))).unwrap();
+    }
+    std::fs::copy(
+        stub_fixtures_dir().join("evolution-chain/1.json"),
+        dir.join("evolution-chain/1.json"),
+    )
+    .unwrap();
+    let (server, endpoint) = start_stub(dir.clone());
+    let translations = Arc::new(AtomicUsize::new(0));
+    let mut app = init_app_with(UpstreamClients::new(
+        PokeApiClient::new(&endpoint),
+        CountingTranslator(translations.clone()),
+    ))
+    .await;
+
+    for name in &["bulbasaur", "ivysaur", "venusaur"] {
+        let resp = test::call_service(
+            &mut app,
+            TestRequest::get()
+                .uri(&format!("/v1/pokemon/{}/evolution", name))
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(200, resp.status());
+    }
+    assert_eq!(1, translations.load(Ordering::SeqCst));
+    server.stop(true).await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_narrative_of_stale_chain_is_not_cached() {
+    let translations = Arc::new(AtomicUsize::new(0));
+    let mut app = init_app_with(UpstreamClients::new(
+        StalePokedex,
+        CountingTranslator(translations.clone()),
+    ))
+    .await;
+
+    for _ in 0..2 {
+        let resp = test::call_service(
+            &mut app,
+            TestRequest::get()
+                .uri("/v1/pokemon/bulbasaur/evolution")
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(200, resp.status());
+    }
+    assert_eq!(2, translations.load(Ordering::SeqCst));
+}
+
+#[actix_rt::test]
+async fn test_species_without_evolution_chain() {
+    let dir = temp_dir("evolution-no-chain");
+    std::fs::create_dir_all(dir.join("pokemon-species")).unwrap();
+    std::fs::copy(
+        "./tests/fixtures/poke_api_valid_response.json",
+        dir.join("pokemon-species/bulbasaur.json"),
+    )
+    .unwrap();
+    let (server, endpoint) = start_stub(dir.clone());
+    let mut app = init_app_with(UpstreamClients::new(
+        PokeApiClient::new(&endpoint),
+        CountingTranslator::default(),
+    ))
+    .await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur/evolution")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(404, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::EvolutionChainNotFound,
+            message: format!(
+                "No evolution chain found when calling PokeApi URL \"{}/api/v2/pokemon-species/bulbasaur\"",
+                endpoint
+            ),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+    server.stop(true).await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_description_source_without_evolution_chains() {
+    let mut app = init_app_with(UpstreamClients::new(Pokedex, CountingTranslator::default())).await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/pikachu/evolution")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(500, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::DescriptionSourceError,
+            message:
+                "Can't get the evolution chain of pikachu: not supported by the description source"
+                    .into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+}
+
+/// Description source without HTTP calls nor evolution chains.
+struct Pokedex;
+
+#[async_trait]
+impl DescriptionSource for Pokedex {
+    async fn get_description(&self, pokemon_name: &str) -> Result<String, DescriptionSourceError> {
+        Err(DescriptionSourceError::NotFound(format!(
+            "No {} in the Pokédex",
+            pokemon_name
+        )))
+    }
+}
+
+/// Description source without HTTP calls, whose evolution chains are all stale.
+struct StalePokedex;
+
+#[async_trait]
+impl DescriptionSource for StalePokedex {
+    async fn get_description(&self, pokemon_name: &str) -> Result<String, DescriptionSourceError> {
+        Ok(format!("{} is a Pokémon.", pokemon_name))
+    }
+
+    async fn get_evolution_chain(
+        &self,
+        _pokemon_name: &str,
+    ) -> Result<EvolutionStage, DescriptionSourceError> {
+        Ok(EvolutionStage {
+            species: "bulbasaur".into(),
+            conditions: vec![],
+            evolves_to: vec![],
+            stale: true,
+        })
+    }
+}
+
+/// Translator uppercasing texts, counting its translations.
+#[derive(Default)]
+struct CountingTranslator(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Translator for CountingTranslator {
+    async fn translate_to(&self, text: &str, _style: &str) -> Result<String, TranslatorError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(text.to_uppercase())
+    }
+}
+
+async fn init_app_with(
+    clients: UpstreamClients,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(
+            cfg,
+            &get_discard_logger(),
+            &SharedState::from_env(),
+            clients,
+        )
+    }))
+    .await
+}
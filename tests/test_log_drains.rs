@@ -0,0 +1,40 @@
+mod common;
+
+use common::{temp_dir, SharedBuffer};
+use pokespeare::log_drains::{Logfmt, RotatingFile};
+use slog::{info, o, Drain, Logger};
+use std::io::Write;
+
+#[test]
+fn test_logfmt_quotes_values_only_when_needed() {
+    let buffer = SharedBuffer::default();
+    let log = Logger::root(
+        Logfmt::new(buffer.clone()).fuse(),
+        o!("upstream" => "poke_api"),
+    );
+
+    info!(log, "Upstream call succeeded"; "status" => 200, "url" => "http://a b");
+
+    let line = buffer.contents();
+    assert!(line.starts_with("ts="));
+    assert!(line.ends_with(
+        " level=INFO msg=\"Upstream call succeeded\" url=\"http://a b\" status=200 upstream=poke_api\n"
+    ));
+}
+
+#[test]
+fn test_rotating_file_keeps_at_most_max_files() {
+    let dir = temp_dir("log-rotation");
+    let path = dir.join("pokespeare.log");
+    let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+
+    for line in &["first\n", "second\n", "third\n", "fourth\n"] {
+        file.write_all(line.as_bytes()).unwrap();
+    }
+
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    assert_eq!("fourth\n", read("pokespeare.log"));
+    assert_eq!("third\n", read("pokespeare.log.1"));
+    assert_eq!("second\n", read("pokespeare.log.2"));
+    assert!(!dir.join("pokespeare.log.3").exists());
+}
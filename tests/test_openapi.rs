@@ -0,0 +1,164 @@
+#![cfg(feature = "server")]
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::{test, test::TestRequest, web, App, HttpResponse};
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::openapi::ApiDoc;
+use pokespeare::services::{self, SharedState};
+use serde_json::Value;
+use utoipa::openapi::PathItemType;
+use utoipa::OpenApi;
+
+/// Operations routed by `config_v1`, to be kept in sync with it: together with the OpenAPI spec, they must match both
+/// ways the routes of the app.
+const V1_OPERATIONS: &[(&str, &str)] = &[
+    ("GET", "/v1/pokemon/{pokemon_name}"),
+    ("GET", "/v1/pokemon/{pokemon_name}/evolution"),
+    ("POST", "/v1/pokemon/batch"),
+    ("POST", "/v1/translate"),
+];
+
+#[actix_rt::test]
+async fn test_openapi_spec_is_served() {
+    let mut app = init_app().await;
+
+    let resp = call(&mut app, TestRequest::get().uri("/openapi.json")).await;
+
+    assert_eq!(200, resp.status());
+    let served_spec: Value = test::read_body_json(resp).await;
+    assert_eq!(
+        serde_json::to_value(ApiDoc::openapi()).unwrap(),
+        served_spec
+    );
+}
+
+#[actix_rt::test]
+async fn test_docs_page_is_served() {
+    let mut app = init_app().await;
+
+    let resp = call(&mut app, TestRequest::get().uri("/docs")).await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        "text/html; charset=utf-8",
+        resp.headers().get("content-type").unwrap()
+    );
+}
+
+/// Every operation of the spec must be routed by the app: unrouted paths would be answered by the default service
+/// (418 here) and unrouted methods with a 405.
+#[actix_rt::test]
+async fn test_every_openapi_operation_is_routed() {
+    let mut app = init_app().await;
+
+    for (path, item) in ApiDoc::openapi().paths.paths {
+        for method in item.operations.keys().map(method_name) {
+            assert_routed(&mut app, method, &path).await;
+            if path.starts_with("/v1") {
+                assert!(
+                    V1_OPERATIONS.contains(&(method, path.as_str())),
+                    "{} {} is missing from V1_OPERATIONS",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+}
+
+/// Every routed operation, versioned or not, must be in the spec.
+#[actix_rt::test]
+async fn test_every_routed_operation_is_documented() {
+    let mut app = init_app().await;
+    let paths = ApiDoc::openapi().paths.paths;
+
+    for (method, path) in V1_OPERATIONS {
+        assert_routed(&mut app, method, path).await;
+        let unversioned = path.strip_prefix("/v1").unwrap();
+        for path in [*path, unversioned] {
+            assert!(
+                paths.get(path).is_some_and(|item| item
+                    .operations
+                    .keys()
+                    .any(|documented| method_name(documented) == *method)),
+                "{} {} is missing from the OpenAPI spec",
+                method,
+                path
+            );
+        }
+    }
+}
+
+async fn assert_routed(
+    app: &mut impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error>,
+    method: &str,
+    path: &str,
+) {
+    let req = match method {
+        "GET" => TestRequest::get(),
+        "POST" => TestRequest::post(),
+        "PUT" => TestRequest::put(),
+        "DELETE" => TestRequest::delete(),
+        "PATCH" => TestRequest::patch(),
+        _ => panic!("Unexpected method {} for {}", method, path),
+    };
+
+    let resp = call(app, req.uri(&fill_path_params(path))).await;
+
+    assert_ne!(418, resp.status(), "{} {} isn't routed", method, path);
+    assert_ne!(405, resp.status(), "{} {} isn't routed", method, path);
+}
+
+fn method_name(method: &PathItemType) -> &'static str {
+    match method {
+        PathItemType::Get => "GET",
+        PathItemType::Post => "POST",
+        PathItemType::Put => "PUT",
+        PathItemType::Delete => "DELETE",
+        PathItemType::Patch => "PATCH",
+        PathItemType::Head => "HEAD",
+        PathItemType::Options => "OPTIONS",
+        PathItemType::Trace => "TRACE",
+        PathItemType::Connect => "CONNECT",
+    }
+}
+
+fn fill_path_params(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if segment.starts_with('{') {
+                "bulbasaur"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn init_app(
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    // Upstreams are never reached: only routing matters here
+    std::env::set_var("POKE_API_ENDPOINT", "http://127.0.0.1:1");
+    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", "http://127.0.0.1:1");
+
+    test::init_service(
+        App::new()
+            .configure(|cfg| {
+                services::config_app(cfg, &get_discard_logger(), &SharedState::from_env())
+            })
+            .default_service(
+                web::route().to(|| HttpResponse::build(StatusCode::IM_A_TEAPOT).finish()),
+            ),
+    )
+    .await
+}
+
+async fn call(
+    app: &mut impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error>,
+    req: TestRequest,
+) -> ServiceResponse {
+    test::call_service(app, req.to_request()).await
+}
@@ -0,0 +1,293 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_web::dev::Server;
+use actix_web::web::{self, Data};
+use actix_web::{test, test::TestRequest, App, HttpRequest, HttpResponse, HttpServer};
+use common::{fixture, start_stub, stub_fixtures_dir, MockUpstream, UppercaseTranslator};
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::poke_api_client::{PokeApiClient, PokeApiClientError, SpeciesCache};
+use pokespeare::services::{self, SharedState, UpstreamClients};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ETAG: &str = "\"bulbasaur-v1\"";
+
+#[actix_rt::test]
+async fn test_fresh_species_are_served_from_cache() {
+    let poke_api = MockPokeApi::start();
+    let client = PokeApiClient::new(&poke_api.url)
+        .with_species_cache(Arc::new(SpeciesCache::new(Duration::from_secs(3600))));
+
+    let first = client.get_random_description("bulbasaur").await.unwrap();
+    let second = client.get_random_description("bulbasaur").await.unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(vec![None], poke_api.requests());
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_species_are_cached_by_species_name_up_to_max_entries() {
+    let (stub, endpoint) = start_stub(stub_fixtures_dir());
+    let species_cache = Arc::new(SpeciesCache::new(Duration::from_secs(3600)).with_max_entries(2));
+    let client = PokeApiClient::new(&endpoint).with_species_cache(species_cache.clone());
+
+    client
+        .get_random_description("charizard-mega-x")
+        .await
+        .unwrap();
+    client.get_random_description("charizard").await.unwrap();
+    let form_len = species_cache.len();
+    client.get_random_description("bulbasaur").await.unwrap();
+    client.get_random_description("pikachu").await.unwrap();
+    let unknown = client.get_random_description("missingno").await;
+
+    // The form and its species share the same entry
+    assert_eq!(1, form_len);
+    assert_eq!(2, species_cache.len());
+    assert!(unknown.is_err());
+    assert_eq!(2, species_cache.len());
+    stub.stop(true).await;
+}
+
+#[actix_rt::test]
+async fn test_expired_species_are_revalidated() {
+    let poke_api = MockPokeApi::start();
+    let client = PokeApiClient::new(&poke_api.url)
+        .with_species_cache(Arc::new(SpeciesCache::new(Duration::from_secs(0))));
+
+    let first = client.get_random_description("bulbasaur").await.unwrap();
+    let (second, metadata) = client
+        .get_random_description_with_metadata("bulbasaur")
+        .await
+        .unwrap();
+
+    assert_eq!(first, second);
+    assert!(!metadata.stale);
+    assert_eq!(Some("Seed Pokémon".into()), metadata.genus);
+    assert_eq!(vec![None, Some(ETAG.to_string())], poke_api.requests());
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_not_modified_response_to_unconditional_request_is_an_error() {
+    let poke_api = MockUpstream::poke_api("bulbasaur", 304, "");
+    let client = PokeApiClient::new(&poke_api.url)
+        .with_species_cache(Arc::new(SpeciesCache::new(Duration::from_secs(3600))));
+
+    let error = client
+        .get_random_description("bulbasaur")
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        format!(
+            "Unexpected response status 304 Not Modified when calling PokeApi URL \"{}/api/v2/pokemon-species/bulbasaur\"",
+            poke_api.url
+        ),
+        error.to_string()
+    );
+    assert!(matches!(error, PokeApiClientError::UnexpectedResponse(_)));
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_stale_species_are_served_while_poke_api_is_down() {
+    let poke_api = MockPokeApi::start();
+    let client = PokeApiClient::new(&poke_api.url)
+        .with_species_cache(Arc::new(SpeciesCache::new(Duration::from_secs(0))));
+
+    let (first, metadata) = client
+        .get_random_description_with_metadata("bulbasaur")
+        .await
+        .unwrap();
+    poke_api.set_down(true);
+    let (stale, stale_metadata) = client
+        .get_random_description_with_metadata("bulbasaur")
+        .await
+        .unwrap();
+    let uncached = client.get_random_description("pikachu").await;
+
+    assert!(!metadata.stale);
+    assert_eq!(first, stale);
+    assert!(stale_metadata.stale);
+    assert_eq!(metadata.genus, stale_metadata.genus);
+    assert!(uncached.is_err());
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_stale_description_is_marked_in_the_response() {
+    let poke_api = MockPokeApi::start();
+    let shared_state = SharedState {
+        species_cache: Some(Arc::new(SpeciesCache::new(Duration::from_secs(0)))),
+        ..SharedState::from_env()
+    };
+    let poke_api_client = PokeApiClient::new(&poke_api.url)
+        .with_species_cache(shared_state.species_cache.clone().unwrap());
+    let clients = UpstreamClients::new(poke_api_client, UppercaseTranslator);
+    let mut app = test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(cfg, &get_discard_logger(), &shared_state, clients)
+    }))
+    .await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=genus")
+            .to_request(),
+    )
+    .await;
+    poke_api.set_down(true);
+    let stale_resp = test::call_service(
+        &mut app,
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur?include=genus")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(None, body.get("stale"));
+    assert_eq!(200, stale_resp.status());
+    let stale_body: Value = test::read_body_json(stale_resp).await;
+    assert_eq!(json!(true), stale_body["stale"]);
+    assert_eq!(body["description"], stale_body["description"]);
+    assert_eq!(json!({ "genus": "Seed Pokémon" }), stale_body["species"]);
+    poke_api.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_stale_description_is_marked_in_every_representation_and_not_cached() {
+    let poke_api = MockPokeApi::start();
+    let species_cache = Arc::new(SpeciesCache::new(Duration::from_secs(0)));
+    let poke_api_client = PokeApiClient::new(&poke_api.url).with_species_cache(species_cache);
+    // Caches the species only, not the description
+    poke_api_client
+        .get_random_description("bulbasaur")
+        .await
+        .unwrap();
+    let clients = UpstreamClients::new(poke_api_client, UppercaseTranslator);
+    let shared_state = SharedState::from_env();
+    let mut app = test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(cfg, &get_discard_logger(), &shared_state, clients)
+    }))
+    .await;
+    let call_accepting = |accept: &str| {
+        TestRequest::get()
+            .uri("/v1/pokemon/bulbasaur")
+            .header("accept", accept)
+            .to_request()
+    };
+
+    poke_api.set_down(true);
+    let xml_resp = test::call_service(&mut app, call_accepting("application/xml")).await;
+    let html_resp = test::call_service(&mut app, call_accepting("text/html")).await;
+    let text_resp = test::call_service(&mut app, call_accepting("text/plain")).await;
+    poke_api.set_down(false);
+    let fresh_resp = test::call_service(&mut app, call_accepting("text/plain")).await;
+
+    assert_eq!(200, xml_resp.status());
+    assert_eq!(
+        "110 - \"Response is Stale\"",
+        xml_resp.headers().get("warning").unwrap()
+    );
+    let xml = String::from_utf8(test::read_body(xml_resp).await.to_vec()).unwrap();
+    assert!(
+        xml.ends_with("</description><stale>true</stale></shakespeareanDescription>\n"),
+        "{}",
+        xml
+    );
+    let html = String::from_utf8(test::read_body(html_resp).await.to_vec()).unwrap();
+    assert!(
+        html.contains("<p class=\"stale\">Possibly outdated: PokeApi is unavailable</p>"),
+        "{}",
+        html
+    );
+    assert_eq!(
+        "110 - \"Response is Stale\"",
+        text_resp.headers().get("warning").unwrap()
+    );
+    assert_eq!(200, fresh_resp.status());
+    assert!(fresh_resp.headers().get("warning").is_none());
+    // Every request called PokeApi API, stale descriptions are not cached
+    assert_eq!(5, poke_api.requests().len());
+    poke_api.stop().await;
+}
+
+/// PokeApi API mock serving Bulbasaur species with an `ETag`, answering `304 Not Modified` to matching conditional
+/// requests and `503 Service Unavailable` to any request once down.
+struct MockPokeApi {
+    url: String,
+    server: Server,
+    state: Data<MockPokeApiState>,
+}
+
+#[derive(Default)]
+struct MockPokeApiState {
+    down: AtomicBool,
+    /// `If-None-Match` header of each request.
+    requests: Mutex<Vec<Option<String>>>,
+}
+
+impl MockPokeApi {
+    fn start() -> Self {
+        let state = Data::new(MockPokeApiState::default());
+        let server_state = state.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(server_state.clone())
+                .route(
+                    "/api/v2/pokemon-species/bulbasaur",
+                    web::get().to(get_species),
+                )
+                .default_service(web::to(|| HttpResponse::NotFound().finish()))
+        })
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let url = format!("http://127.0.0.1:{}", server.addrs()[0].port());
+        Self {
+            url,
+            server: server.run(),
+            state,
+        }
+    }
+
+    fn set_down(&self, down: bool) {
+        self.state.down.store(down, Ordering::SeqCst);
+    }
+
+    fn requests(&self) -> Vec<Option<String>> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    async fn stop(self) {
+        self.server.stop(true).await;
+    }
+}
+
+async fn get_species(req: HttpRequest, state: Data<MockPokeApiState>) -> HttpResponse {
+    let if_none_match = req
+        .headers()
+        .get("if-none-match")
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    state.requests.lock().unwrap().push(if_none_match.clone());
+
+    if state.down.load(Ordering::SeqCst) {
+        return HttpResponse::ServiceUnavailable().finish();
+    }
+    if if_none_match.as_deref() == Some(ETAG) {
+        return HttpResponse::NotModified().header("etag", ETAG).finish();
+    }
+    HttpResponse::Ok()
+        .header("etag", ETAG)
+        .content_type("application/json")
+        .body(fixture("poke_api_species_response.json"))
+}
@@ -0,0 +1,301 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::{test, test::TestRequest, App};
+use async_trait::async_trait;
+use common::{MockUpstream, UppercaseTranslator};
+use pokespeare::api_keys::{ApiKeyConfig, ApiKeys};
+use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode, RateLimitedBy};
+use pokespeare::fun_translations_client::{FunTranslationsClient, FunTranslationsTier};
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::rate_limiting::{RateLimitConfig, RateLimiter};
+use pokespeare::services::{self, BatchConfig, SharedState, UpstreamClients};
+use pokespeare::services_api_models::{
+    ShakespeareanDescriptionsBatchApiRequest, ShakespeareanDescriptionsBatchApiResponse,
+    ShakespeareanDescriptionsBatchApiResult,
+};
+use pokespeare::upstreams::{DescriptionSource, DescriptionSourceError};
+
+#[actix_rt::test]
+async fn test_exceeded_client_rate() {
+    let mut app = init_app(rate_limiter(2), None).await;
+
+    let first_resp = call_batch_service(&mut app, "10.0.0.1", None, None).await;
+    let second_resp = call_batch_service(&mut app, "10.0.0.1", None, None).await;
+    let resp = call_batch_service(&mut app, "10.0.0.1", None, None).await;
+
+    // The requests are allowed and then rejected by the API service because of the empty batch
+    assert_eq!(400, first_resp.status());
+    assert_eq!(("2", "1"), rate_limit_headers(&first_resp));
+    assert_eq!(400, second_resp.status());
+    assert_eq!(("2", "0"), rate_limit_headers(&second_resp));
+    assert_eq!(429, resp.status());
+    assert_eq!(("2", "0"), rate_limit_headers(&resp));
+    assert_eq!("1", header(&resp, "retry-after"));
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::TooManyRequests,
+            message: "Client exceeded the rate of 60 requests per minute, retry in 1 seconds"
+                .into(),
+            limited_by: Some(RateLimitedBy::Local),
+        },
+        test::read_body_json(resp).await
+    );
+}
+
+#[actix_rt::test]
+async fn test_clients_are_limited_by_ip() {
+    let mut app = init_app(rate_limiter(1), None).await;
+
+    let first_resp = call_batch_service(&mut app, "10.0.0.1", None, None).await;
+    let other_client_resp = call_batch_service(&mut app, "10.0.0.2", None, None).await;
+    let resp = call_batch_service(&mut app, "10.0.0.1", None, None).await;
+
+    assert_eq!(400, first_resp.status());
+    assert_eq!(400, other_client_resp.status());
+    assert_eq!(429, resp.status());
+}
+
+#[actix_rt::test]
+async fn test_forwarded_for_of_untrusted_proxies_is_ignored() {
+    let mut app = init_app(rate_limiter(1), None).await;
+
+    let first_resp = call_batch_service(&mut app, "10.0.0.1", Some("1.1.1.1"), None).await;
+    let resp = call_batch_service(&mut app, "10.0.0.1", Some("2.2.2.2"), None).await;
+
+    assert_eq!(400, first_resp.status());
+    assert_eq!(429, resp.status());
+}
+
+#[actix_rt::test]
+async fn test_forwarded_for_of_trusted_proxies_is_honored() {
+    let mut app = init_app(rate_limiter(1), None).await;
+
+    let first_resp = call_batch_service(&mut app, "10.0.0.9", Some("1.1.1.1"), None).await;
+    let other_client_resp =
+        call_batch_service(&mut app, "10.0.0.9", Some("2.2.2.2, 10.0.0.8"), None).await;
+    // The leftmost address is forged by the client itself, only the ones appended by the trusted proxies count
+    let resp = call_batch_service(&mut app, "10.0.0.9", Some("3.3.3.3, 1.1.1.1"), None).await;
+
+    assert_eq!(400, first_resp.status());
+    assert_eq!(400, other_client_resp.status());
+    assert_eq!(429, resp.status());
+}
+
+#[actix_rt::test]
+async fn test_authenticated_clients_are_limited_by_api_key() {
+    let api_keys = ApiKeys::new(vec![ApiKeyConfig {
+        name: "team-roster".into(),
+        key: "s3cr3t".into(),
+        requests_per_minute: None,
+        daily_quota: None,
+    }]);
+    let mut app = init_app(rate_limiter(1), Some(api_keys)).await;
+
+    let first_resp = call_batch_service(&mut app, "10.0.0.1", None, Some("s3cr3t")).await;
+    let resp = call_batch_service(&mut app, "10.0.0.2", None, Some("s3cr3t")).await;
+
+    assert_eq!(400, first_resp.status());
+    assert_eq!(429, resp.status());
+}
+
+#[actix_rt::test]
+async fn test_batches_take_a_token_per_pokemon_not_cached() {
+    let shared_state = SharedState {
+        rate_limiter: Some(Data::new(rate_limiter(2))),
+        ..SharedState::from_env()
+    };
+    let mut app = test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(
+            cfg,
+            &get_discard_logger(),
+            &shared_state,
+            UpstreamClients::new(EchoDescriptionSource, UppercaseTranslator),
+        )
+    }))
+    .await;
+    let batch = |names: &[&str]| {
+        TestRequest::post()
+            .uri("/v1/pokemon/batch")
+            .peer_addr("10.0.0.1:40000".parse().unwrap())
+            .set_json(&ShakespeareanDescriptionsBatchApiRequest {
+                names: names.iter().map(|name| name.to_string()).collect(),
+            })
+            .to_request()
+    };
+
+    let resp = test::call_service(&mut app, batch(&["bulbasaur", "ivysaur", "venusaur"])).await;
+    let next_resp = test::call_service(&mut app, batch(&["bulbasaur"])).await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(("2", "0"), rate_limit_headers(&resp));
+    let results = test::read_body_json::<ShakespeareanDescriptionsBatchApiResponse, _>(resp)
+        .await
+        .results;
+    assert_eq!(
+        vec![200, 200, 429],
+        results.iter().map(|item| item.status).collect::<Vec<_>>()
+    );
+    assert!(results[2].quota_limited);
+    assert!(matches!(
+        &results[2].result,
+        ShakespeareanDescriptionsBatchApiResult::Error(ApiErrorResponseBody {
+            code: ApiErrorResponseCode::TooManyRequests,
+            limited_by: Some(RateLimitedBy::Local),
+            ..
+        })
+    ));
+    // Even batches of cached Pokémon take a token
+    assert_eq!(429, next_resp.status());
+}
+
+#[actix_rt::test]
+async fn test_quota_limited_batch_items_take_no_token() {
+    let fun_translations = MockUpstream::fun_translations(429, "");
+    let shared_state = SharedState {
+        rate_limiter: Some(Data::new(rate_limiter(5))),
+        batch_config: Data::new(BatchConfig {
+            max_size: 20,
+            concurrency: 1,
+        }),
+        ..SharedState::from_env()
+    };
+    let clients = UpstreamClients::new(
+        EchoDescriptionSource,
+        FunTranslationsClient::new(&fun_translations.url),
+    );
+    let mut app = test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(cfg, &get_discard_logger(), &shared_state, clients)
+    }))
+    .await;
+
+    let resp = test::call_service(
+        &mut app,
+        TestRequest::post()
+            .uri("/v1/pokemon/batch")
+            .peer_addr("10.0.0.1:40000".parse().unwrap())
+            .set_json(&ShakespeareanDescriptionsBatchApiRequest {
+                names: vec!["bulbasaur".into(), "ivysaur".into(), "venusaur".into()],
+            })
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    // Only the token of the request itself, paying the first Pokémon, is taken
+    assert_eq!(("5", "4"), rate_limit_headers(&resp));
+    let results = test::read_body_json::<ShakespeareanDescriptionsBatchApiResponse, _>(resp)
+        .await
+        .results;
+    assert!(results.iter().all(|item| item.quota_limited));
+    assert_eq!(1, fun_translations.hits());
+    fun_translations.stop().await;
+}
+
+#[test]
+fn test_rate_depends_on_fun_translations_tier() {
+    std::env::remove_var("POKESPEARE_RATE_LIMIT_PER_MINUTE");
+    std::env::remove_var("POKESPEARE_PAID_RATE_LIMIT_PER_MINUTE");
+    let default_free_config = RateLimitConfig::from_env(FunTranslationsTier::Free).unwrap();
+    let default_paid_config = RateLimitConfig::from_env(FunTranslationsTier::Paid).unwrap();
+    std::env::set_var("POKESPEARE_RATE_LIMIT_PER_MINUTE", "5");
+    std::env::set_var("POKESPEARE_PAID_RATE_LIMIT_PER_MINUTE", "600");
+    let free_config = RateLimitConfig::from_env(FunTranslationsTier::Free).unwrap();
+    let paid_config = RateLimitConfig::from_env(FunTranslationsTier::Paid).unwrap();
+    std::env::set_var("POKESPEARE_RATE_LIMIT_PER_MINUTE", "0");
+    let disabled_free_config = RateLimitConfig::from_env(FunTranslationsTier::Free);
+    std::env::remove_var("POKESPEARE_RATE_LIMIT_PER_MINUTE");
+    std::env::remove_var("POKESPEARE_PAID_RATE_LIMIT_PER_MINUTE");
+
+    assert_eq!(
+        (10, 10),
+        (
+            default_free_config.requests_per_minute,
+            default_free_config.burst
+        )
+    );
+    assert_eq!(
+        (120, 120),
+        (
+            default_paid_config.requests_per_minute,
+            default_paid_config.burst
+        )
+    );
+    assert_eq!((5, 5), (free_config.requests_per_minute, free_config.burst));
+    assert_eq!(
+        (600, 600),
+        (paid_config.requests_per_minute, paid_config.burst)
+    );
+    assert!(disabled_free_config.is_none());
+}
+
+/// Description source without HTTP calls, describing any Pokémon by its name.
+struct EchoDescriptionSource;
+
+#[async_trait]
+impl DescriptionSource for EchoDescriptionSource {
+    async fn get_description(&self, pokemon_name: &str) -> Result<String, DescriptionSourceError> {
+        Ok(format!("It's {}.", pokemon_name))
+    }
+}
+
+fn rate_limiter(burst: u32) -> RateLimiter {
+    RateLimiter::new(RateLimitConfig {
+        requests_per_minute: 60,
+        burst,
+        trusted_proxies: vec!["10.0.0.8".parse().unwrap(), "10.0.0.9".parse().unwrap()],
+    })
+}
+
+fn header<'a>(resp: &'a ServiceResponse, name: &str) -> &'a str {
+    resp.headers().get(name).unwrap().to_str().unwrap()
+}
+
+fn rate_limit_headers(resp: &ServiceResponse) -> (&str, &str) {
+    (
+        header(resp, "ratelimit-limit"),
+        header(resp, "ratelimit-remaining"),
+    )
+}
+
+async fn init_app(
+    rate_limiter: RateLimiter,
+    api_keys: Option<ApiKeys>,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    // Upstreams are never reached: requests are either rejected or invalid
+    std::env::set_var("POKE_API_ENDPOINT", "http://127.0.0.1:1");
+    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", "http://127.0.0.1:1");
+
+    let shared_state = SharedState {
+        api_keys: api_keys.map(Data::new),
+        rate_limiter: Some(Data::new(rate_limiter)),
+        ..SharedState::from_env()
+    };
+    test::init_service(
+        App::new().configure(|cfg| services::config_app(cfg, &get_discard_logger(), &shared_state)),
+    )
+    .await
+}
+
+async fn call_batch_service(
+    app: &mut impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error>,
+    peer: &str,
+    forwarded_for: Option<&str>,
+    key: Option<&str>,
+) -> ServiceResponse {
+    let mut req = TestRequest::post()
+        .uri("/v1/pokemon/batch")
+        .peer_addr(format!("{}:40000", peer).parse().unwrap())
+        .set_json(&ShakespeareanDescriptionsBatchApiRequest { names: vec![] });
+    if let Some(forwarded_for) = forwarded_for {
+        req = req.header("x-forwarded-for", forwarded_for);
+    }
+    if let Some(key) = key {
+        req = req.header("x-api-key", key);
+    }
+    test::call_service(app, req.to_request()).await
+}
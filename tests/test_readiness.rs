@@ -0,0 +1,29 @@
+#![cfg(feature = "server")]
+
+use actix_web::{test, test::TestRequest, App};
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::services::{self, SharedState};
+
+#[actix_rt::test]
+async fn test_readiness_is_failed_by_shutdown() {
+    // Upstreams are never reached: only the readiness probe is called
+    std::env::set_var("POKE_API_ENDPOINT", "http://127.0.0.1:1");
+    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", "http://127.0.0.1:1");
+    let shared_state = SharedState::from_env();
+    let mut app = test::init_service(
+        App::new().configure(|cfg| services::config_app(cfg, &get_discard_logger(), &shared_state)),
+    )
+    .await;
+
+    let ready_resp =
+        test::call_service(&mut app, TestRequest::get().uri("/ready").to_request()).await;
+    shared_state.readiness.fail();
+    let resp = test::call_service(&mut app, TestRequest::get().uri("/ready").to_request()).await;
+
+    assert_eq!(200, ready_resp.status());
+    assert_eq!(503, resp.status());
+    assert_eq!(
+        serde_json::json!({ "status": "shutting_down" }),
+        test::read_body_json::<serde_json::Value, _>(resp).await
+    );
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::{test, test::TestRequest, App, HttpResponse};
+use common::{fixture, stop_upstreams, MockUpstream};
+use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode, RateLimitedBy};
+use pokespeare::fun_translations_client::FunTranslationsClient;
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::poke_api_client::PokeApiClient;
+use pokespeare::services::{self, BatchConfig, SharedState, UpstreamClients};
+use pokespeare::services_api_models::{
+    ShakespeareanDescriptionApiResponse, ShakespeareanDescriptionsBatchApiItem,
+    ShakespeareanDescriptionsBatchApiRequest, ShakespeareanDescriptionsBatchApiResponse,
+    ShakespeareanDescriptionsBatchApiResult,
+};
+
+const SHAKESPEAREAN_DESCRIPTION: &str =
+    "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.";
+
+#[actix_rt::test]
+async fn test_partial_failure() {
+    let upstreams = mock_upstreams(&["bulbasaur"], 200);
+    let mut app = init_app(&upstreams, SharedState::from_env()).await;
+
+    let resp = call_batch_service(&mut app, &["bulbasaur", "missingno"]).await;
+
+    assert_eq!(200, resp.status());
+    let results = test::read_body_json::<ShakespeareanDescriptionsBatchApiResponse, _>(resp)
+        .await
+        .results;
+    assert_eq!(description_item("bulbasaur", false), results[0]);
+    assert_eq!("missingno", results[1].name);
+    assert_eq!(404, results[1].status);
+    assert!(!results[1].quota_limited);
+    assert!(matches!(
+        &results[1].result,
+        ShakespeareanDescriptionsBatchApiResult::Error(ApiErrorResponseBody {
+            code: ApiErrorResponseCode::PokeApiError,
+            ..
+        })
+    ));
+    assert_eq!(1, upstreams.1.hits());
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_invalid_names_are_rejected_before_the_cache() {
+    let upstreams = mock_upstreams(&["bulbasaur"], 200);
+    let mut app = init_app(&upstreams, SharedState::from_env()).await;
+
+    let resp = call_batch_service(&mut app, &["bulbasaur", "bulbasaur#evolution"]).await;
+
+    assert_eq!(200, resp.status());
+    let results = test::read_body_json::<ShakespeareanDescriptionsBatchApiResponse, _>(resp)
+        .await
+        .results;
+    assert_eq!(description_item("bulbasaur", false), results[0]);
+    assert_eq!(
+        ShakespeareanDescriptionsBatchApiItem {
+            name: "bulbasaur#evolution".into(),
+            status: 400,
+            cached: false,
+            quota_limited: false,
+            result: ShakespeareanDescriptionsBatchApiResult::Error(ApiErrorResponseBody {
+                code: ApiErrorResponseCode::InvalidRequest,
+                message: "Invalid Pokémon name \"bulbasaur#evolution\", expected lowercase letters, digits and dashes only".into(),
+                limited_by: None,
+            }),
+        },
+        results[1]
+    );
+    assert_eq!(1, upstreams.1.hits());
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_items_after_exceeded_quota_are_not_requested() {
+    let upstreams = mock_upstreams(&["bulbasaur", "ivysaur", "venusaur"], 429);
+    let shared_state = SharedState {
+        batch_config: Data::new(BatchConfig {
+            max_size: 20,
+            concurrency: 1,
+        }),
+        ..SharedState::from_env()
+    };
+    let mut app = init_app(&upstreams, shared_state).await;
+
+    let resp = call_batch_service(&mut app, &["bulbasaur", "ivysaur", "venusaur"]).await;
+
+    assert_eq!(200, resp.status());
+    let results = test::read_body_json::<ShakespeareanDescriptionsBatchApiResponse, _>(resp)
+        .await
+        .results;
+    assert_eq!(1, upstreams.1.hits());
+    for (name, item) in ["bulbasaur", "ivysaur", "venusaur"].iter().zip(&results) {
+        assert_eq!(name, &item.name);
+        assert_eq!(429, item.status);
+        assert!(item.quota_limited);
+        assert!(matches!(
+            &item.result,
+            ShakespeareanDescriptionsBatchApiResult::Error(ApiErrorResponseBody {
+                code: ApiErrorResponseCode::TooManyRequests,
+                limited_by: Some(RateLimitedBy::Upstream),
+                ..
+            })
+        ));
+    }
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_cache_hits_are_served_without_upstream_calls() {
+    let upstreams = mock_upstreams(&["bulbasaur", "ivysaur"], 200);
+    let mut app = init_app(&upstreams, SharedState::from_env()).await;
+
+    let first_resp = call_batch_service(&mut app, &["bulbasaur"]).await;
+    let resp = call_batch_service(&mut app, &["ivysaur", "bulbasaur", "ivysaur"]).await;
+
+    assert_eq!(200, first_resp.status());
+    assert_eq!(200, resp.status());
+    assert_eq!(2, upstreams.1.hits());
+    assert_eq!(
+        ShakespeareanDescriptionsBatchApiResponse {
+            results: vec![
+                description_item("ivysaur", false),
+                description_item("bulbasaur", true),
+                description_item("ivysaur", false),
+            ]
+        },
+        test::read_body_json(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
+
+#[actix_rt::test]
+async fn test_empty_batch_is_rejected() {
+    let upstreams = mock_upstreams(&[], 200);
+    let mut app = init_app(&upstreams, SharedState::from_env()).await;
+
+    let resp = call_batch_service(&mut app, &[]).await;
+
+    assert_eq!(400, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::InvalidRequest,
+            message: "Expected from 1 to 20 Pokémon names, got 0".into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+    stop_upstreams(upstreams).await;
+}
+
+/// Starts the PokeApi API mock, knowing only the given Pokémon, and the FunTranslations API one, answering with the
+/// given status.
+fn mock_upstreams(pokemon_names: &[&str], translation_status: u16) -> (MockUpstream, MockUpstream) {
+    let paths = pokemon_names
+        .iter()
+        .map(|name| format!("/api/v2/pokemon-species/{}", name))
+        .collect::<Vec<_>>();
+    let poke_api = MockUpstream::start_with(move |req| {
+        if paths.contains(&req.path) {
+            HttpResponse::Ok().body(fixture("poke_api_valid_response.json"))
+        } else {
+            HttpResponse::NotFound().finish()
+        }
+    });
+    let fun_translations = MockUpstream::fun_translations(
+        translation_status,
+        &fixture("fun_translations_valid_response.json"),
+    );
+    (poke_api, fun_translations)
+}
+
+fn description_item(name: &str, cached: bool) -> ShakespeareanDescriptionsBatchApiItem {
+    ShakespeareanDescriptionsBatchApiItem {
+        name: name.into(),
+        status: 200,
+        cached,
+        quota_limited: false,
+        result: ShakespeareanDescriptionsBatchApiResult::Description(
+            ShakespeareanDescriptionApiResponse {
+                name: name.into(),
+                description: SHAKESPEAREAN_DESCRIPTION.into(),
+                species_name: None,
+                stale: false,
+                species: None,
+            },
+        ),
+    }
+}
+
+async fn init_app(
+    (poke_api, fun_translations): &(MockUpstream, MockUpstream),
+    shared_state: SharedState,
+) -> impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error> {
+    let clients = UpstreamClients::new(
+        PokeApiClient::new(&poke_api.url),
+        FunTranslationsClient::new(&fun_translations.url),
+    );
+    test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(cfg, &get_discard_logger(), &shared_state, clients)
+    }))
+    .await
+}
+
+async fn call_batch_service(
+    app: &mut impl Service<Request = Request, Response = ServiceResponse, Error = actix_web::Error>,
+    names: &[&str],
+) -> ServiceResponse {
+    let req = TestRequest::post()
+        .uri("/v1/pokemon/batch")
+        .set_json(&ShakespeareanDescriptionsBatchApiRequest {
+            names: names.iter().map(|&name| name.into()).collect(),
+        })
+        .to_request();
+    test::call_service(app, req).await
+}
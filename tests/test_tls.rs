@@ -0,0 +1,95 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_web::{App, HttpServer};
+use common::temp_dir;
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::services::{self, SharedState};
+use pokespeare::tls::TlsConfig;
+use rcgen::generate_simple_self_signed;
+use std::path::Path;
+use std::time::Duration;
+
+#[actix_rt::test]
+async fn test_plain_and_tls_listeners_with_reloaded_certificate() {
+    let dir = temp_dir("tls-reload");
+    let first_cert = write_self_signed_cert(&dir);
+    let tls_config = TlsConfig {
+        listen_addr: "127.0.0.1:0".into(),
+        cert_file: dir.join("cert.pem"),
+        key_file: dir.join("key.pem"),
+        reload_interval: Duration::from_secs(0),
+    };
+    // Upstreams are never reached: only the readiness probe is called
+    std::env::set_var("POKE_API_ENDPOINT", "http://127.0.0.1:1");
+    std::env::set_var("FUN_TRANSLATIONS_API_ENDPOINT", "http://127.0.0.1:1");
+    let shared_state = SharedState::from_env();
+    let server = HttpServer::new(move || {
+        App::new().configure(|cfg| services::config_app(cfg, &get_discard_logger(), &shared_state))
+    })
+    .workers(1)
+    .bind("127.0.0.1:0")
+    .unwrap()
+    .bind_rustls(
+        &tls_config.listen_addr,
+        tls_config.server_config(&get_discard_logger()).unwrap(),
+    )
+    .unwrap();
+    let (plain_port, tls_port) = (server.addrs()[0].port(), server.addrs()[1].port());
+    let server = server.run();
+
+    let plain_status = get_ready(&format!("http://localhost:{}/ready", plain_port), None).await;
+    let tls_url = format!("https://localhost:{}/ready", tls_port);
+    let first_cert_status = get_ready(&tls_url, Some(&first_cert)).await;
+    let second_cert = write_self_signed_cert(&dir);
+    let reloaded_cert_status = get_ready(&tls_url, Some(&second_cert)).await;
+    let replaced_cert_status = get_ready(&tls_url, Some(&first_cert)).await;
+
+    assert_eq!(200, plain_status.unwrap());
+    assert_eq!(200, first_cert_status.unwrap());
+    assert_eq!(200, reloaded_cert_status.unwrap());
+    assert!(replaced_cert_status.is_err());
+    server.stop(true).await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_invalid_certificate_is_rejected() {
+    let dir = temp_dir("tls-invalid");
+    std::fs::write(dir.join("cert.pem"), "not a certificate").unwrap();
+    std::fs::write(dir.join("key.pem"), "not a key").unwrap();
+    let tls_config = TlsConfig {
+        listen_addr: "127.0.0.1:0".into(),
+        cert_file: dir.join("cert.pem"),
+        key_file: dir.join("key.pem"),
+        reload_interval: Duration::from_secs(10),
+    };
+
+    let error = tls_config
+        .server_config(&get_discard_logger())
+        .err()
+        .unwrap();
+
+    assert!(error.starts_with("No PEM certificates in"), "{}", error);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Writes a new self-signed certificate for `localhost` with its key, returning the PEM of the certificate.
+fn write_self_signed_cert(dir: &Path) -> String {
+    let cert = generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let cert_pem = cert.serialize_pem().unwrap();
+    std::fs::write(dir.join("cert.pem"), &cert_pem).unwrap();
+    std::fs::write(dir.join("key.pem"), cert.serialize_private_key_pem()).unwrap();
+    cert_pem
+}
+
+/// Calls the readiness probe trusting only the given certificate, returning the response status.
+async fn get_ready(url: &str, trusted_cert: Option<&str>) -> Result<u16, reqwest::Error> {
+    let mut client = reqwest::Client::builder();
+    if let Some(cert) = trusted_cert {
+        client = client.add_root_certificate(reqwest::Certificate::from_pem(cert.as_bytes())?);
+    }
+    let resp = client.build()?.get(url).send().await?;
+    Ok(resp.status().as_u16())
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_web::{test, test::TestRequest, App};
+use common::{fixture, MockUpstream};
+use opentelemetry::global;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::trace::TracerProvider;
+use opentelemetry::testing::trace::new_test_exporter;
+use opentelemetry::trace::{SpanId, SpanKind, TraceId};
+use pokespeare::fun_translations_client::FunTranslationsClient;
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::poke_api_client::PokeApiClient;
+use pokespeare::services::{self, SharedState, UpstreamClients};
+
+const INCOMING_TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const INCOMING_SPAN_ID: &str = "00f067aa0ba902b7";
+
+#[actix_rt::test]
+async fn test_spans_are_exported_and_trace_context_propagated_to_upstreams() {
+    let (exporter, exported_spans, _shutdown) = new_test_exporter();
+    let _provider = global::set_tracer_provider(
+        TracerProvider::builder()
+            .with_simple_exporter(exporter)
+            .build(),
+    );
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let poke_api =
+        MockUpstream::poke_api("bulbasaur", 200, &fixture("poke_api_valid_response.json"));
+    let fun_translations =
+        MockUpstream::fun_translations(200, &fixture("fun_translations_valid_response.json"));
+    let clients = UpstreamClients::new(
+        PokeApiClient::new(&poke_api.url),
+        FunTranslationsClient::new(&fun_translations.url),
+    );
+
+    let mut app = test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(
+            cfg,
+            &get_discard_logger(),
+            &SharedState::from_env(),
+            clients,
+        )
+    }))
+    .await;
+    let req = TestRequest::get()
+        .uri("/pokemon/bulbasaur")
+        .header(
+            "traceparent",
+            format!("00-{}-{}-01", INCOMING_TRACE_ID, INCOMING_SPAN_ID),
+        )
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+
+    assert_eq!(200, resp.status());
+    let spans = exported_spans.try_iter().collect::<Vec<_>>();
+    let span = |name: &str| spans.iter().find(|s| s.name == name).unwrap();
+    let server_span = span("GET /pokemon/{pokemon_name}");
+    let poke_api_span = span("get_random_description");
+    let fun_translations_span = span("translate");
+
+    assert_eq!(3, spans.len());
+    assert_eq!(SpanKind::Server, server_span.span_kind);
+    assert_eq!(
+        SpanId::from_hex(INCOMING_SPAN_ID),
+        server_span.parent_span_id
+    );
+    for client_span in &[poke_api_span, fun_translations_span] {
+        assert_eq!(SpanKind::Client, client_span.span_kind);
+        assert_eq!(
+            server_span.span_context.span_id(),
+            client_span.parent_span_id
+        );
+    }
+    for span in &spans {
+        assert_eq!(
+            TraceId::from_hex(INCOMING_TRACE_ID),
+            span.span_context.trace_id()
+        );
+    }
+    for upstream in &[&poke_api, &fun_translations] {
+        let requests = upstream.requests();
+        assert_eq!(1, requests.len());
+        let traceparent = requests[0].header("traceparent").unwrap();
+        assert!(
+            traceparent.starts_with(&format!("00-{}-", INCOMING_TRACE_ID))
+                && traceparent.ends_with("-01")
+                && !traceparent.contains(INCOMING_SPAN_ID),
+            "{}",
+            traceparent
+        );
+    }
+    poke_api.stop().await;
+    fun_translations.stop().await;
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_web::http::Method;
+use actix_web::{dev::ServiceResponse, test, test::TestRequest, App};
+use common::{fixture, MockUpstream};
+use pokespeare::errors::{ApiErrorResponseBody, ApiErrorResponseCode};
+use pokespeare::fun_translations_client::FunTranslationsClient;
+use pokespeare::log_helpers::get_discard_logger;
+use pokespeare::poke_api_client::PokeApiClient;
+use pokespeare::services::{self, SharedState, UpstreamClients};
+use pokespeare::services_api_models::{TranslationApiRequest, TranslationApiResponse};
+
+/// Endpoint of the upstreams never called by the tests.
+const UNREACHABLE_ENDPOINT: &str = "http://127.0.0.1:1";
+
+#[actix_rt::test]
+async fn test_happy_path() {
+    let fun_translations = MockUpstream::start(
+        Method::GET,
+        "/translate/yoda.json",
+        200,
+        fixture("fun_translations_valid_response.json"),
+    );
+
+    let resp = call_translate_text_service(
+        TestRequest::post().set_json(&TranslationApiRequest {
+            text: "Sparky is fast".into(),
+            style: "yoda".into(),
+        }),
+        &fun_translations.url,
+    )
+    .await;
+
+    assert_eq!(200, resp.status());
+    assert_eq!(
+        TranslationApiResponse {
+            style: "yoda".into(),
+            translation: "A strange seed wast planted on its back at birth. The plant sprouts and grows with this pokémon.".into(),
+        },
+        test::read_body_json(resp).await
+    );
+    let requests = fun_translations.requests();
+    assert_eq!(1, requests.len());
+    assert_eq!(Some("Sparky is fast"), requests[0].param("text"));
+    fun_translations.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_too_many_requests_on_fun_translations_api() {
+    let fun_translations = MockUpstream::fun_translations(429, "");
+
+    let resp = call_translate_text_service(
+        TestRequest::post().set_payload(r#"{"text": "Sparky is fast"}"#),
+        &fun_translations.url,
+    )
+    .await;
+
+    assert_eq!(429, resp.status());
+    assert_eq!(
+        ApiErrorResponseCode::TooManyRequests,
+        test::read_body_json::<ApiErrorResponseBody, _>(resp)
+            .await
+            .code
+    );
+    assert_eq!(1, fun_translations.hits());
+    fun_translations.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_too_long_text_is_rejected() {
+    let resp = call_translate_text_service(
+        TestRequest::post().set_json(&TranslationApiRequest {
+            text: "a".repeat(1001),
+            style: "shakespeare".into(),
+        }),
+        UNREACHABLE_ENDPOINT,
+    )
+    .await;
+
+    assert_eq!(400, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::InvalidRequest,
+            message: "Text exceeds 1000 characters, got 1001".into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+}
+
+#[actix_rt::test]
+async fn test_invalid_style_is_rejected() {
+    let resp = call_translate_text_service(
+        TestRequest::post().set_json(&TranslationApiRequest {
+            text: "Sparky is fast".into(),
+            style: "../shakespeare".into(),
+        }),
+        UNREACHABLE_ENDPOINT,
+    )
+    .await;
+
+    assert_eq!(400, resp.status());
+    assert_eq!(
+        ApiErrorResponseCode::InvalidRequest,
+        test::read_body_json::<ApiErrorResponseBody, _>(resp)
+            .await
+            .code
+    );
+}
+
+#[actix_rt::test]
+async fn test_too_large_body_is_rejected() {
+    let resp = call_translate_text_service(
+        TestRequest::post().set_json(&TranslationApiRequest {
+            text: "a".repeat(20 * 1024),
+            style: "shakespeare".into(),
+        }),
+        UNREACHABLE_ENDPOINT,
+    )
+    .await;
+
+    assert_eq!(413, resp.status());
+    assert_eq!(
+        ApiErrorResponseBody {
+            code: ApiErrorResponseCode::PayloadTooLarge,
+            message: "Request body exceeds 16384 bytes".into(),
+            limited_by: None,
+        },
+        test::read_body_json(resp).await
+    );
+}
+
+#[actix_rt::test]
+async fn test_malformed_body_is_rejected() {
+    let resp = call_translate_text_service(
+        TestRequest::post().set_payload("{\"text\":"),
+        UNREACHABLE_ENDPOINT,
+    )
+    .await;
+
+    assert_eq!(400, resp.status());
+    assert_eq!(
+        ApiErrorResponseCode::InvalidRequest,
+        test::read_body_json::<ApiErrorResponseBody, _>(resp)
+            .await
+            .code
+    );
+}
+
+async fn call_translate_text_service(
+    req: TestRequest,
+    fun_translations_endpoint: &str,
+) -> ServiceResponse {
+    let clients = UpstreamClients::new(
+        PokeApiClient::new(UNREACHABLE_ENDPOINT),
+        FunTranslationsClient::new(fun_translations_endpoint),
+    );
+    let mut app = test::init_service(App::new().configure(|cfg| {
+        services::config_app_with(
+            cfg,
+            &get_discard_logger(),
+            &SharedState::from_env(),
+            clients,
+        )
+    }))
+    .await;
+    let req = req
+        .uri("/v1/translate")
+        .header("content-type", "application/json")
+        .to_request();
+    test::call_service(&mut app, req).await
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use actix_web::{test, App};
+use common::{start_stub, stub_fixtures_dir};
+use pokespeare::fun_translations_client::FunTranslationsClient;
+use pokespeare::poke_api_client::PokeApiClient;
+use pokespeare::upstream_stub::{self, StubConfig};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+#[actix_rt::test]
+async fn test_species_are_served_from_fixtures() {
+    let mut app = test::init_service(
+        App::new().configure(|cfg| upstream_stub::config_stub(cfg, stub_config())),
+    )
+    .await;
+
+    let found = test::TestRequest::get()
+        .uri("/api/v2/pokemon-species/pikachu")
+        .send_request(&mut app)
+        .await;
+    let not_found = test::TestRequest::get()
+        .uri("/api/v2/pokemon-species/missingno")
+        .send_request(&mut app)
+        .await;
+
+    assert_eq!(200, found.status());
+    let species: Value = test::read_body_json(found).await;
+    assert_eq!(json!("pikachu"), species["name"]);
+    assert_eq!(404, not_found.status());
+}
+
+#[actix_rt::test]
+async fn test_text_is_translated_with_style_dictionary() {
+    let mut app = test::init_service(
+        App::new().configure(|cfg| upstream_stub::config_stub(cfg, stub_config())),
+    )
+    .await;
+
+    let translated = test::TestRequest::post()
+        .uri("/translate/shakespeare.json")
+        .set_form(&[("text", "You are fast, when it flies.")])
+        .send_request(&mut app)
+        .await;
+    let unknown_style = test::TestRequest::post()
+        .uri("/translate/klingon.json")
+        .set_form(&[("text", "You are fast.")])
+        .send_request(&mut app)
+        .await;
+
+    assert_eq!(200, translated.status());
+    let body: Value = test::read_body_json(translated).await;
+    assert_eq!(
+        json!("Thee art swift, at which hour 't doth fly."),
+        body["contents"]["translated"]
+    );
+    assert_eq!(404, unknown_style.status());
+}
+
+#[actix_rt::test]
+async fn test_faults_and_latency_are_injected() {
+    let faulty_config = |error_rate, too_many_requests_rate| StubConfig {
+        latency: Duration::from_millis(50),
+        error_rate,
+        too_many_requests_rate,
+        ..stub_config()
+    };
+    let mut failing_app = test::init_service(
+        App::new().configure(|cfg| upstream_stub::config_stub(cfg, faulty_config(1.0, 0.0))),
+    )
+    .await;
+    let mut throttled_app = test::init_service(
+        App::new().configure(|cfg| upstream_stub::config_stub(cfg, faulty_config(0.0, 1.0))),
+    )
+    .await;
+
+    let started_at = Instant::now();
+    let failed = test::TestRequest::get()
+        .uri("/api/v2/pokemon-species/pikachu")
+        .send_request(&mut failing_app)
+        .await;
+    let elapsed = started_at.elapsed();
+    let throttled = test::TestRequest::post()
+        .uri("/translate/shakespeare.json")
+        .set_form(&[("text", "You are fast.")])
+        .send_request(&mut throttled_app)
+        .await;
+
+    assert_eq!(500, failed.status());
+    assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+    assert_eq!(429, throttled.status());
+    let body: Value = test::read_body_json(throttled).await;
+    assert_eq!(json!(429), body["error"]["code"]);
+}
+
+#[test]
+fn test_invalid_fault_rates_are_rejected() {
+    for rate in &["NaN", "-0.1", "1.5"] {
+        std::env::set_var("UPSTREAM_STUB_ERROR_RATE", rate);
+        let config = std::panic::catch_unwind(StubConfig::from_env);
+        std::env::remove_var("UPSTREAM_STUB_ERROR_RATE");
+
+        assert!(config.is_err(), "{}", rate);
+    }
+    std::env::set_var("UPSTREAM_STUB_TOO_MANY_REQUESTS_RATE", "1");
+    let config = StubConfig::from_env();
+    std::env::remove_var("UPSTREAM_STUB_TOO_MANY_REQUESTS_RATE");
+
+    assert_eq!(1.0, config.too_many_requests_rate);
+}
+
+#[actix_rt::test]
+async fn test_clients_run_against_stub() {
+    let (server, endpoint) = start_stub(stub_fixtures_dir());
+
+    let description = PokeApiClient::new(&endpoint)
+        .get_random_description("bulbasaur")
+        .await
+        .unwrap();
+    let translation = FunTranslationsClient::new(&endpoint)
+        .translate(&description)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        "A strange seed was planted on its back at birth. The plant sprouts and grows with this POKéMON.",
+        description
+    );
+    assert_eq!(
+        "A bawbling grain was sown on its back at birth. The plant doth sprout and doth grow with this POKéMON.",
+        translation
+    );
+    server.stop(true).await;
+}
+
+#[actix_rt::test]
+async fn test_forms_are_resolved_to_their_species_against_stub() {
+    let (server, endpoint) = start_stub(stub_fixtures_dir());
+    let client = PokeApiClient::new(&endpoint);
+
+    let (_, form_metadata) = client
+        .get_random_description_with_metadata("charizard-mega-x")
+        .await
+        .unwrap();
+    let (_, species_metadata) = client
+        .get_random_description_with_metadata("charizard")
+        .await
+        .unwrap();
+    let unknown = client
+        .get_random_description("missingno")
+        .await
+        .unwrap_err();
+
+    assert_eq!(Some("charizard".into()), form_metadata.name);
+    assert_eq!(species_metadata, form_metadata);
+    assert_eq!(
+        format!(
+            "HTTP status client error (404 Not Found) for url ({}/api/v2/pokemon-species/missingno)",
+            endpoint
+        ),
+        unknown.to_string()
+    );
+    server.stop(true).await;
+}
+
+fn stub_config() -> StubConfig {
+    StubConfig {
+        fixtures_dir: stub_fixtures_dir(),
+        latency: Duration::from_millis(0),
+        error_rate: 0.0,
+        too_many_requests_rate: 0.0,
+    }
+}